@@ -56,7 +56,9 @@ fn gen_api_files() -> std::io::Result<()> {
     client_file.write_all(b"/*eslint-disable*/\n")?;
     client_file.write_all(b"export type Option<T> = T | null;\n")?;
     client_file.write_all(b"export type BoxedErr = string\n")?;
-    client_file.write_all(b"export type ForceValidateError<T> = string\n")?;
+    client_file.write_all(
+        b"export type ForceValidateError<T> = { field_name: string; index: number | null; kind: string; message: string };\n",
+    )?;
     client_file.write_all(
         b"// @ts-ignore\nexport type HashMap<K extends string | number, T> = { [key: K]: T };\n",
     )?;
@@ -71,5 +73,36 @@ fn gen_api_files() -> std::io::Result<()> {
     client_file.write_all(custom_error::gen_typescript().as_bytes())?;
     client_file
         .write_all(RootQuery::codegen(&CodegenOption::Client { error: "Error" }).as_bytes())?;
+    // 批次請求的客戶端，伺服器端見 routes::handle_batch
+    client_file.write_all(
+        r#"// 選用：同一輪事件迴圈中發出的請求合併成一次 HTTP 請求，結果依序分給各呼叫
+export abstract class BatchedRootQueryFetcher extends RootQueryFetcher {
+    abstract fetchBatch(queries: Object[]): Promise<string[]>;
+    private queue: { query: Object, resolve: (result: string) => void, reject: (err: any) => void }[] = [];
+    fetchResult(query: Object): Promise<string> {
+        return new Promise((resolve, reject) => {
+            this.queue.push({ query, resolve, reject });
+            if (this.queue.length == 1) {
+                setTimeout(() => this.flush(), 0);
+            }
+        });
+    }
+    private async flush(): Promise<void> {
+        // 與伺服器端的 MAX_BATCH 一致，超過的留待下一批
+        const queue = this.queue.splice(0, 32);
+        if (this.queue.length > 0) {
+            setTimeout(() => this.flush(), 0);
+        }
+        try {
+            const results = await this.fetchBatch(queue.map(q => q.query));
+            queue.forEach((q, i) => q.resolve(results[i]));
+        } catch (err) {
+            queue.forEach(q => q.reject(err));
+        }
+    }
+}
+"#
+        .as_bytes(),
+    )?;
     Ok(())
 }
@@ -0,0 +1,155 @@
+use force::format::format;
+use force::instance_defs::Bond;
+use force::validate::ValidatorTrait;
+use force::{parse, Bondee, Category, Definition, ForceError};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+// 讓看板設計者離線檢查力語言，例：
+// force lint 看板.force
+// force check 看板.force 新聞 範例1.json 範例2.json
+
+#[derive(StructOpt, Debug)]
+#[structopt(about = "力語言命令行工具")]
+enum Root {
+    #[structopt(about = "檢查力語言原始碼")]
+    Lint { file: PathBuf },
+    #[structopt(about = "印出格式化後的原始碼")]
+    Fmt { file: PathBuf },
+    #[structopt(about = "以指定分類驗證範例文章")]
+    Check {
+        file: PathBuf,
+        category: String,
+        samples: Vec<PathBuf>,
+    },
+}
+
+// 離線時無從查詢鍵結指向的文章，一律放行
+struct Validator;
+#[async_trait::async_trait]
+impl ValidatorTrait for Validator {
+    type OtherError = String;
+    async fn validate_bond(&self, _bondee: &Bondee, _data: &Bond) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn read(file: &PathBuf) -> Result<String, String> {
+    std::fs::read_to_string(file).map_err(|e| format!("讀取 {:?} 失敗：{}", file, e))
+}
+
+fn lint(source: &str) -> Result<(), String> {
+    let mut ok = true;
+    let definitions = force::parser::Parser::new(source)
+        .parse_definitions()
+        .map_err(|e| e.to_string())?;
+    let mut field_sets = HashSet::new();
+    for definition in &definitions {
+        if let Definition::FieldSet { name, fields: _ } = definition {
+            if !field_sets.insert(name) {
+                println!("欄位集 {} 重複定義", name);
+                ok = false;
+            }
+        }
+    }
+    let categories = force::parser::Parser::new(source)
+        .parse_category_list()
+        .map_err(|e| e.to_string())?;
+    let mut names = HashSet::new();
+    for category in &categories {
+        if !names.insert(&category.name) {
+            println!("分類 {} 重複定義", category.name);
+            ok = false;
+        }
+        if field_sets.contains(&category.name) {
+            println!("分類 {} 與欄位集撞名", category.name);
+            ok = false;
+        }
+        let mut fields = HashSet::new();
+        for field in &category.fields {
+            if !fields.insert(&field.name) {
+                println!("分類 {} 的欄位 {} 重複定義", category.name, field.name);
+                ok = false;
+            }
+        }
+    }
+    match parse(source) {
+        Ok(_) => (),
+        Err(ForceError::InvalidBond {
+            not_found_categories,
+            not_found_families,
+        }) => {
+            for c in not_found_categories {
+                println!("鍵結指向不存在的分類 {}", c);
+            }
+            for f in not_found_families {
+                println!("鍵結指向不存在的分類族 {}", f);
+            }
+            ok = false;
+        }
+        Err(e) => return Err(e.to_string()),
+    }
+    if ok {
+        Ok(())
+    } else {
+        Err("力語言檢查未通過".to_owned())
+    }
+}
+
+async fn check(category: &Category, samples: &[PathBuf]) -> Result<(), String> {
+    let mut ok = true;
+    for sample in samples {
+        let data: serde_json::Value = serde_json::from_str(&read(sample)?)
+            .map_err(|e| format!("{:?} 不是合法的 JSON：{}", sample, e))?;
+        match Validator.validate_category(category, &data).await {
+            Ok(()) => println!("{:?} 通過", sample),
+            Err(errors) => {
+                ok = false;
+                println!("{:?} 未通過", sample);
+                for err in errors {
+                    match err.index {
+                        Some(i) => println!("    {}[{}]：{}", err.field_name, i, err.code),
+                        None => println!("    {}：{}", err.field_name, err.code),
+                    }
+                }
+            }
+        }
+    }
+    if ok {
+        Ok(())
+    } else {
+        Err("範例文章驗證未通過".to_owned())
+    }
+}
+
+async fn run(root: Root) -> Result<(), String> {
+    match root {
+        Root::Lint { file } => lint(&read(&file)?),
+        Root::Fmt { file } => {
+            let formatted = format(&read(&file)?).map_err(|e| e.to_string())?;
+            print!("{}", formatted);
+            Ok(())
+        }
+        Root::Check {
+            file,
+            category,
+            samples,
+        } => {
+            let force = parse(&read(&file)?).map_err(|e| e.to_string())?;
+            let category = force
+                .categories
+                .get(&category)
+                .ok_or(format!("找不到分類 {}", category))?;
+            check(category, &samples).await
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(msg) = run(Root::from_args()).await {
+        eprintln!("{}", msg);
+        std::process::exit(1);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::lexer;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
@@ -16,6 +17,24 @@ pub enum Bondee {
     },
 }
 
+/// 鍵結欄位允許的標籤
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TypeScriptify)]
+pub struct BondTags {
+    pub tags: Vec<String>,
+    // 是否必須帶有標籤
+    pub required: bool,
+}
+
+/// 投票欄位的定義，票數另存於資料庫，文章內容中該欄位恆爲 null
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TypeScriptify)]
+pub struct Poll {
+    pub options: Vec<String>,
+    // 是否可複選
+    pub multiple: bool,
+    // 發文後幾小時截止，None 表示永不截止
+    pub close_hours: Option<usize>,
+}
+
 fn serialize_regex<S>(re: &Option<Regex>, s: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -42,7 +61,8 @@ where
 
 #[derive(Debug, Serialize, Deserialize, TypeScriptify, Clone)]
 pub enum BasicDataType {
-    Bond(Bondee),
+    // 未宣告標籤時可帶任意標籤或不帶
+    Bond(Bondee, Option<BondTags>),
     OneLine,
     #[serde(
         serialize_with = "serialize_regex",
@@ -50,19 +70,24 @@ pub enum BasicDataType {
     )]
     Text(Option<Regex>), // 正則表達式
     Number,
+    Poll(Poll),
+    // 值爲已上傳圖片的 id
+    Image,
 }
 
 impl PartialEq for BasicDataType {
     fn eq(&self, other: &BasicDataType) -> bool {
         match (self, other) {
-            (BasicDataType::Bond(bondee), BasicDataType::Bond(other_bondee)) => {
-                bondee == other_bondee
+            (BasicDataType::Bond(bondee, tags), BasicDataType::Bond(other_bondee, other_tags)) => {
+                bondee == other_bondee && tags == other_tags
             }
             (BasicDataType::OneLine, BasicDataType::OneLine) => true,
             (BasicDataType::Text(Some(regex)), BasicDataType::Text(Some(other_regex))) => {
                 regex.as_str() == other_regex.as_str()
             }
             (BasicDataType::Number, BasicDataType::Number) => true,
+            (BasicDataType::Poll(poll), BasicDataType::Poll(other_poll)) => poll == other_poll,
+            (BasicDataType::Image, BasicDataType::Image) => true,
             _ => false,
         }
     }
@@ -99,13 +124,49 @@ impl From<BasicDataType> for DataType {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, TypeScriptify)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TypeScriptify)]
+pub enum DefaultValue {
+    Number(i64),
+    String(String),
+}
+
+impl DefaultValue {
+    pub fn to_value(&self) -> Value {
+        match self {
+            DefaultValue::Number(n) => Value::from(*n),
+            DefaultValue::String(s) => Value::from(s.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TypeScriptify)]
 pub struct Field {
     pub datatype: DataType,
     pub name: String,
+    // 給編輯器顯示的說明
+    #[serde(default)]
+    pub description: Option<String>,
+    // 輸入框的提示文字
+    #[serde(default)]
+    pub placeholder: Option<String>,
+    // 文章缺省該欄位時填入的值，解析時已檢查過型別
+    #[serde(default)]
+    pub default: Option<DefaultValue>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, TypeScriptify)]
+impl Field {
+    pub fn new(datatype: DataType, name: String) -> Self {
+        Field {
+            datatype,
+            name,
+            description: None,
+            placeholder: None,
+            default: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TypeScriptify)]
 pub struct Category {
     pub source: String,
     pub name: String,
@@ -113,6 +174,50 @@ pub struct Category {
     pub fields: Vec<Field>,
 }
 
+impl Category {
+    /// 文章缺省（或爲 null）的非陣列欄位填入 null ，其中的選填欄位若有預設值則填入預設值
+    ///
+    /// 必填欄位的預設值只是編輯器的初始內容，缺省時仍應驗證失敗
+    pub fn fill_defaults(&self, data: &mut Value) {
+        let json = match data.as_object_mut() {
+            Some(json) => json,
+            None => return,
+        };
+        for field in &self.fields {
+            if let DataType::Array { .. } = field.datatype {
+                continue;
+            }
+            let value = json.entry(field.name.clone()).or_insert(Value::Null);
+            match (&field.datatype, &field.default) {
+                (DataType::Optional(_), Some(default)) if value.is_null() => {
+                    *value = default.to_value()
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// 原始碼中的頂層定義，展開繼承與共用欄位之後才成爲 Category
+#[derive(Debug)]
+pub enum Definition {
+    // 分類名 : 父分類或欄位集, ... @[分類族] { 欄位 }
+    Category {
+        category: Category,
+        extends: Vec<String>,
+    },
+    // 欄位集 名稱 { 欄位 }
+    FieldSet {
+        name: String,
+        fields: Vec<Field>,
+    },
+    // @分類族 { 欄位 }，該族所有分類皆具備這些欄位
+    FamilyFields {
+        family: String,
+        fields: Vec<Field>,
+    },
+}
+
 pub type Categories = HashMap<String, Arc<Category>>;
 
 #[derive(Debug)]
@@ -138,11 +243,51 @@ pub enum ForceError {
     InvalidRegex {
         regex: String,
     },
+    InvalidDefault {
+        field: String,
+    },
+    InvalidExtend {
+        category: String,
+        parent: String,
+    },
+    CyclicExtend {
+        category: String,
+    },
+    InvalidPoll {
+        field: String,
+    },
 }
 
 impl fmt::Display for ForceError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "力語言錯誤")
+        write!(f, "力語言錯誤：")?;
+        match self {
+            ForceError::InvalidBond {
+                not_found_categories,
+                not_found_families,
+            } => write!(
+                f,
+                "鍵結指向不存在的分類 {:?} 或分類族 {:?}",
+                not_found_categories, not_found_families
+            ),
+            ForceError::NonExpect { expect, fact } => {
+                write!(f, "預期 {:?}，卻得到 {:?}", expect, fact)
+            }
+            ForceError::NoMeet { expect, fact } => write!(f, "預期 {}，卻得到 {:?}", expect, fact),
+            ForceError::InvalidRegex { regex } => write!(f, "不合法的正則表達式 /{}/", regex),
+            ForceError::InvalidDefault { field } => {
+                write!(f, "欄位 {} 的預設值與型別不符", field)
+            }
+            ForceError::InvalidExtend { category, parent } => {
+                write!(f, "分類 {} 繼承了不存在的分類或欄位集 {}", category, parent)
+            }
+            ForceError::CyclicExtend { category } => {
+                write!(f, "分類 {} 的繼承關係形成循環", category)
+            }
+            ForceError::InvalidPoll { field } => {
+                write!(f, "投票欄位 {} 不可爲選填或陣列，且選項不可重複", field)
+            }
+        }
     }
 }
 
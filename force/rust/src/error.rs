@@ -1,11 +1,15 @@
 use crate::defs::BasicDataType;
 use regex::Regex;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use serde_json::{value::Number, Error as JsonError, Value};
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use ValidationErrorCode::*;
 
 #[derive(Debug)]
 pub struct ValidationError<OtherError> {
     pub field_name: String,
+    // 陣列欄位中出錯元素的索引，錯誤屬於整個欄位時爲 None
+    pub index: Option<usize>,
     pub code: ValidationErrorCode<OtherError>,
 }
 #[derive(Debug)]
@@ -22,6 +26,56 @@ pub enum ValidationErrorCode<OtherError> {
         max: usize,
         actual: usize,
     },
+    TagRequired,
+    TagNotAllowed(String),
+}
+
+impl<E> ValidationErrorCode<E> {
+    /// 供前端辨識的錯誤種類
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Other(_) => "Other",
+            NotI64(_) => "NotI64",
+            NotOneline(_) => "NotOneline",
+            RegexFail(..) => "RegexFail",
+            Json(_) => "Json",
+            TypeMismatch(..) => "TypeMismatch",
+            NotArray(_) => "NotArray",
+            ArrayLengthMismatch { .. } => "ArrayLengthMismatch",
+            TagRequired => "TagRequired",
+            TagNotAllowed(_) => "TagNotAllowed",
+        }
+    }
+}
+
+impl<E: Display> Display for ValidationErrorCode<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Other(e) => write!(f, "{}", e),
+            NotI64(n) => write!(f, "{} 不是整數", n),
+            NotOneline(_) => write!(f, "單行欄位不可換行"),
+            RegexFail(regex, _) => write!(f, "不符合正則表達式 {}", regex),
+            Json(e) => write!(f, "JSON 格式錯誤：{}", e),
+            TypeMismatch(t, v) => write!(f, "型別不符，預期 {:?}，得到 {}", t, v),
+            NotArray(v) => write!(f, "預期陣列，得到 {}", v),
+            ArrayLengthMismatch { min, max, actual } => {
+                write!(f, "陣列長度應介於 {} ~ {}，實際爲 {}", min, max, actual)
+            }
+            TagRequired => write!(f, "鍵結必須帶有標籤"),
+            TagNotAllowed(tag) => write!(f, "不允許的鍵結標籤 {}", tag),
+        }
+    }
+}
+
+impl<E: Display> Serialize for ValidationError<E> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut state = s.serialize_struct("ValidationError", 4)?;
+        state.serialize_field("field_name", &self.field_name)?;
+        state.serialize_field("index", &self.index)?;
+        state.serialize_field("kind", self.code.kind())?;
+        state.serialize_field("message", &self.code.to_string())?;
+        state.end()
+    }
 }
 
 impl<E: Display + Debug> Display for ValidationError<E> {
@@ -0,0 +1,198 @@
+use crate::defs::*;
+use crate::parser::Parser;
+
+// 將力語言原始碼整理成統一格式
+
+const INDENT: &str = "    ";
+
+fn format_bondee(bondee: &Bondee) -> String {
+    match bondee {
+        Bondee::All => "*".to_owned(),
+        Bondee::Choices { category, family } => {
+            let choices: Vec<String> = category
+                .iter()
+                .cloned()
+                .chain(family.iter().map(|f| format!("@{}", f)))
+                .collect();
+            choices.join(", ")
+        }
+    }
+}
+
+pub fn format_basic_datatype(t: &BasicDataType) -> String {
+    match t {
+        BasicDataType::Number => "數字".to_owned(),
+        BasicDataType::Image => "圖片".to_owned(),
+        BasicDataType::OneLine => "單行".to_owned(),
+        BasicDataType::Text(None) => "文本".to_owned(),
+        BasicDataType::Text(Some(regex)) => {
+            // 去掉解析器自動加上的 (?s)
+            let s = regex.as_str();
+            format!("文本/{}/", s.strip_prefix("(?s)").unwrap_or(s))
+        }
+        BasicDataType::Poll(poll) => format!(
+            "{}({}){}",
+            if poll.multiple { "複選" } else { "單選" },
+            poll.options.join(", "),
+            match poll.close_hours {
+                Some(hours) => format!("~{}", hours),
+                None => String::new(),
+            }
+        ),
+        BasicDataType::Bond(bondee, None) => format!("鍵結[{}]", format_bondee(bondee)),
+        BasicDataType::Bond(bondee, Some(tags)) => format!(
+            "鍵結[{}]({}){}",
+            format_bondee(bondee),
+            tags.tags.join(", "),
+            if tags.required { "!" } else { "" }
+        ),
+    }
+}
+
+fn format_annotations(field: &Field) -> String {
+    let mut ret = String::new();
+    if let Some(description) = &field.description {
+        ret.push_str(&format!(" #說明:\"{}\"", description));
+    }
+    if let Some(placeholder) = &field.placeholder {
+        ret.push_str(&format!(" #提示:\"{}\"", placeholder));
+    }
+    match &field.default {
+        Some(DefaultValue::Number(n)) => ret.push_str(&format!(" #預設:{}", n)),
+        Some(DefaultValue::String(s)) => ret.push_str(&format!(" #預設:\"{}\"", s)),
+        None => (),
+    }
+    ret
+}
+
+pub fn format_field(field: &Field) -> String {
+    let decl = match &field.datatype {
+        DataType::Single(t) => format!("{} {}", format_basic_datatype(t), field.name),
+        DataType::Optional(t) => format!("{} {}?", format_basic_datatype(t), field.name),
+        DataType::Array { t, min, max } => format!(
+            "{} {}[{}~{}]",
+            format_basic_datatype(t),
+            field.name,
+            min,
+            max
+        ),
+    };
+    decl + &format_annotations(field)
+}
+
+fn format_fields(fields: &[Field]) -> String {
+    if fields.len() == 0 {
+        return " {}".to_owned();
+    }
+    let mut ret = " {\n".to_owned();
+    for field in fields {
+        ret.push_str(INDENT);
+        ret.push_str(&format_field(field));
+        ret.push('\n');
+    }
+    ret.push('}');
+    ret
+}
+
+fn format_category_head(category: &Category, extends: &[String]) -> String {
+    let mut ret = category.name.clone();
+    if extends.len() > 0 {
+        ret.push_str(&format!(" : {}", extends.join(", ")));
+    }
+    if category.family.len() > 0 {
+        ret.push_str(&format!(" @[{}]", category.family.join(", ")));
+    }
+    ret
+}
+
+pub fn format_category(category: &Category) -> String {
+    format_category_head(category, &[]) + &format_fields(&category.fields)
+}
+
+pub fn format_definition(definition: &Definition) -> String {
+    match definition {
+        Definition::Category { category, extends } => {
+            format_category_head(category, extends) + &format_fields(&category.fields)
+        }
+        Definition::FieldSet { name, fields } => {
+            format!("欄位集 {}", name) + &format_fields(fields)
+        }
+        Definition::FamilyFields { family, fields } => {
+            format!("@{}", family) + &format_fields(fields)
+        }
+    }
+}
+
+/// 格式化整份力語言原始碼，定義依原始碼順序排列，不展開繼承
+pub fn format(source: &str) -> ForceResult<String> {
+    let definitions = Parser::new(source).parse_definitions()?;
+    let definitions: Vec<String> = definitions.iter().map(format_definition).collect();
+    let mut ret = definitions.join("\n\n");
+    ret.push('\n');
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    #[test]
+    fn test_format() -> ForceResult<()> {
+        let source = "
+            新聞 @[轉載,外部] {單行 記者 #提示:\"本名或筆名\"  文本/.{1,256}/ 內文 數字 日期? #預設:0 圖片 照片?}
+            空 {}
+            留言 { 鍵結[ 新聞,@轉載 ] 原文[1~3] 鍵結[*]( 支持,反對 )! 立場 複選(甲,乙)~24 午餐 }";
+        let formatted = format(source)?;
+        assert_eq!(
+            formatted,
+            "新聞 @[轉載, 外部] {
+    單行 記者 #提示:\"本名或筆名\"
+    文本/.{1,256}/ 內文
+    數字 日期? #預設:0
+    圖片 照片?
+}
+
+空 {}
+
+留言 {
+    鍵結[新聞, @轉載] 原文[1~3]
+    鍵結[*](支持, 反對)! 立場
+    複選(甲, 乙)~24 午餐
+}
+"
+        );
+        // 格式化後語意不變
+        let before = parse(source)?;
+        let after = parse(&formatted)?;
+        for (name, category) in &before.categories {
+            assert_eq!(category.fields, after.categories.get(name).unwrap().fields);
+        }
+        assert_eq!(format(&formatted)?, formatted);
+        Ok(())
+    }
+    #[test]
+    fn test_format_definitions() -> ForceResult<()> {
+        let source =
+            "欄位集 來源{單行 來源} @轉載{單行 原作者} 新聞:來源,文章 @[轉載]{文本 內文} 文章{}";
+        let formatted = format(source)?;
+        assert_eq!(
+            formatted,
+            "欄位集 來源 {
+    單行 來源
+}
+
+@轉載 {
+    單行 原作者
+}
+
+新聞 : 來源, 文章 @[轉載] {
+    文本 內文
+}
+
+文章 {}
+"
+        );
+        assert_eq!(format(&formatted)?, formatted);
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use crate::defs::*;
+use serde_json::{json, Map, Value};
+
+// 將力語言分類轉譯爲 JSON Schema (draft-07)，供外部工具、機器人檢查文章內容
+
+const DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+// 解析器會替正則表達式加上 (?s) 旗標，但 JSON Schema 採用 ECMA 262 正則，不支援行內旗標
+fn regex_pattern(regex: &regex::Regex) -> String {
+    let s = regex.as_str();
+    s.strip_prefix("(?s)").unwrap_or(s).to_owned()
+}
+
+fn bond_schema(bondee: &Bondee, tags: &Option<BondTags>) -> Value {
+    let mut tag_schema = json!({ "type": ["string", "null"] });
+    let mut required = vec!["energy", "target_article"];
+    if let Some(tags) = tags {
+        if tags.required {
+            tag_schema = json!({ "enum": tags.tags });
+            required.push("tag");
+        } else {
+            let mut choices: Vec<Value> = tags.tags.iter().map(|t| json!(t)).collect();
+            choices.push(Value::Null);
+            tag_schema = json!({ "enum": choices });
+        }
+    }
+    json!({
+        "type": "object",
+        "properties": {
+            "energy": {
+                "type": "integer",
+                "minimum": i16::MIN,
+                "maximum": i16::MAX,
+            },
+            "target_article": { "type": "integer" },
+            "tag": tag_schema,
+        },
+        "required": required,
+        // 鍵結對象無法以 JSON Schema 表達，以擴充關鍵字附上
+        "x-force-bondee": bondee,
+    })
+}
+
+pub fn basic_datatype_to_json_schema(t: &BasicDataType) -> Value {
+    match t {
+        BasicDataType::Number => json!({ "type": "integer" }),
+        // 圖片是否存在、是否屬於作者無法以 JSON Schema 表達
+        BasicDataType::Image => json!({ "type": "integer", "x-force-image": true }),
+        BasicDataType::OneLine => json!({
+            "type": "string",
+            "pattern": "^[^\\n]*$",
+        }),
+        BasicDataType::Text(None) => json!({ "type": "string" }),
+        BasicDataType::Text(Some(regex)) => json!({
+            "type": "string",
+            "pattern": regex_pattern(regex),
+        }),
+        BasicDataType::Bond(bondee, tags) => bond_schema(bondee, tags),
+        // 票數另存，文章內容中恆爲 null
+        BasicDataType::Poll(poll) => json!({
+            "type": "null",
+            "x-force-poll": poll,
+        }),
+    }
+}
+
+pub fn datatype_to_json_schema(t: &DataType) -> Value {
+    match t {
+        DataType::Single(t) => basic_datatype_to_json_schema(t),
+        DataType::Optional(t) => json!({
+            "anyOf": [basic_datatype_to_json_schema(t), { "type": "null" }],
+        }),
+        DataType::Array { t, min, max } => json!({
+            "type": "array",
+            "items": basic_datatype_to_json_schema(t),
+            "minItems": min,
+            "maxItems": max,
+        }),
+    }
+}
+
+pub fn category_to_json_schema(category: &Category) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in &category.fields {
+        let mut schema = datatype_to_json_schema(&field.datatype);
+        if let Some(description) = &field.description {
+            schema["description"] = json!(description);
+        }
+        if let Some(default) = &field.default {
+            schema["default"] = default.to_value();
+        }
+        properties.insert(field.name.clone(), schema);
+        // 選填欄位缺省時視同 null
+        if let DataType::Optional(_) = field.datatype {
+            continue;
+        }
+        required.push(Value::String(field.name.clone()));
+    }
+    json!({
+        "$schema": DRAFT,
+        "title": category.name,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// 回傳以分類名稱爲鍵的 JSON Schema 物件
+pub fn force_to_json_schema(force: &Force) -> Value {
+    let mut schemas = Map::new();
+    for (name, category) in &force.categories {
+        schemas.insert(name.clone(), category_to_json_schema(category));
+    }
+    Value::Object(schemas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_category};
+    #[test]
+    fn test_category_schema() -> ForceResult<()> {
+        let source = "測試 {單行 標題 文本/^.{3,5}$/ 內文 數字 數? 鍵結[*] 原文[1~2]}";
+        let category = parse_category(source)?;
+        let schema = category_to_json_schema(&category);
+        assert_eq!(schema["title"], "測試");
+        assert_eq!(schema["required"], json!(["標題", "內文", "原文"]));
+        assert_eq!(schema["properties"]["標題"]["type"], "string");
+        assert_eq!(schema["properties"]["內文"]["pattern"], "^.{3,5}$");
+        assert_eq!(
+            schema["properties"]["數"],
+            json!({ "anyOf": [{ "type": "integer" }, { "type": "null" }] })
+        );
+        let bond = &schema["properties"]["原文"];
+        assert_eq!(bond["type"], "array");
+        assert_eq!(bond["minItems"], 1);
+        assert_eq!(bond["maxItems"], 2);
+        assert_eq!(bond["items"]["x-force-bondee"], json!("All"));
+        Ok(())
+    }
+    #[test]
+    fn test_annotation_schema() -> ForceResult<()> {
+        let category = parse_category("測試 {單行 標題 #說明:\"文章標題\" #預設:\"無題\"}")?;
+        let schema = category_to_json_schema(&category);
+        assert_eq!(schema["properties"]["標題"]["description"], "文章標題");
+        assert_eq!(schema["properties"]["標題"]["default"], "無題");
+        Ok(())
+    }
+    #[test]
+    fn test_force_schema() -> ForceResult<()> {
+        let force = parse("新聞 {單行 記者} 留言 {鍵結[新聞] 本體 鍵結[*](支持, 反對)! 立場}")?;
+        let schema = force_to_json_schema(&force);
+        assert_eq!(schema.as_object().unwrap().len(), 2);
+        assert_eq!(
+            schema["留言"]["properties"]["本體"]["x-force-bondee"],
+            json!({ "Choices": { "category": ["新聞"], "family": [] } })
+        );
+        let stance = &schema["留言"]["properties"]["立場"];
+        assert_eq!(
+            stance["properties"]["tag"],
+            json!({ "enum": ["支持", "反對"] })
+        );
+        assert_eq!(
+            stance["required"],
+            json!(["energy", "target_article", "tag"])
+        );
+        Ok(())
+    }
+}
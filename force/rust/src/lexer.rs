@@ -30,6 +30,12 @@ pub enum Token {
     QuestionMark,
     #[token("~")]
     Tilde,
+    #[token("(")]
+    LeftParenthesis,
+    #[token(")")]
+    RightParenthesis,
+    #[token("!")]
+    Bang,
 
     // 域型別
     #[token("單行")]
@@ -40,11 +46,26 @@ pub enum Token {
     Number,
     #[token("鍵結")]
     Bond,
+    #[token("圖片")]
+    Image,
+
+    #[token("單選")]
+    SingleChoice,
+    #[token("複選")]
+    MultipleChoice,
+
+    // 欄位集，可供多個分類共用
+    #[token("欄位集")]
+    FieldSet,
 
     // 正則表達式
     #[regex("/[^/]+/", extract_regex)]
     Regex(String),
 
+    // 字串，用於欄位的說明、提示與預設值
+    #[regex("\"[^\"]*\"", extract_str)]
+    Str(String),
+
     // 鍵結的符號
     #[token("*")]
     Star,
@@ -53,7 +74,11 @@ pub enum Token {
 
     // 識別子，只能是中文、英文、數字、底線
     // TODO: 增強識別子的限制
-    #[regex("[^\\s/\\[\\]\\}\\{\\?\\~,#@:]+", get_string)]
+    // ! 與 " 只在開頭時有特殊意義，不開頭的仍可用於識別子，以相容舊有的力
+    #[regex(
+        "[^\\s/\\[\\]\\}\\{\\(\\)\\?\\~!,#@:\"][^\\s/\\[\\]\\}\\{\\(\\)\\?\\~,#@:]*",
+        get_string
+    )]
     Identifier(String),
 
     // 整數，詞法解析順位優先於識別子
@@ -77,6 +102,11 @@ fn extract_regex(lex: &mut Lexer<Token>) -> String {
     s[1..(s.len() - 1)].to_string()
 }
 
+fn extract_str(lex: &mut Lexer<Token>) -> String {
+    let s = lex.slice();
+    s[1..(s.len() - 1)].to_string()
+}
+
 pub fn lexer(s: &str) -> Vec<(Token, Span)> {
     let lex = Token::lexer(s);
     let mut ret: Vec<(Token, Span)> = lex.spanned().collect();
@@ -108,7 +138,7 @@ mod tests {
     }
     #[test]
     fn test_special_character() {
-        let mut lexer = Token::lexer("{}[],#:@?~");
+        let mut lexer = Token::lexer("{}[],#:@?~()!");
         assert_eq!(lexer.next(), Some(Token::LeftCurlyBrace));
         assert_eq!(lexer.next(), Some(Token::RightCurlyBrace));
         assert_eq!(lexer.next(), Some(Token::LeftSquareBracket));
@@ -119,16 +149,35 @@ mod tests {
         assert_eq!(lexer.next(), Some(Token::At));
         assert_eq!(lexer.next(), Some(Token::QuestionMark));
         assert_eq!(lexer.next(), Some(Token::Tilde));
+        assert_eq!(lexer.next(), Some(Token::LeftParenthesis));
+        assert_eq!(lexer.next(), Some(Token::RightParenthesis));
+        assert_eq!(lexer.next(), Some(Token::Bang));
+        assert_eq!(lexer.next(), None);
+    }
+    #[test]
+    fn test_identifier_with_bang_quote() {
+        let mut lexer = Token::lexer("注意! 他說\"好\" !\"\"");
+        assert_eq!(lexer.next(), Some(Token::Identifier("注意!".to_owned())));
+        assert_eq!(
+            lexer.next(),
+            Some(Token::Identifier("他說\"好\"".to_owned()))
+        );
+        assert_eq!(lexer.next(), Some(Token::Bang));
+        assert_eq!(lexer.next(), Some(Token::Str("".to_owned())));
         assert_eq!(lexer.next(), None);
     }
     #[test]
     fn test_keyword() {
-        let mut lexer = Token::lexer("單行 文本 數字 鍵結 輸能");
+        let mut lexer = Token::lexer("單行 文本 數字 鍵結 圖片 單選 複選 輸能 欄位集");
         assert_eq!(lexer.next(), Some(Token::OneLine));
         assert_eq!(lexer.next(), Some(Token::Text));
         assert_eq!(lexer.next(), Some(Token::Number));
         assert_eq!(lexer.next(), Some(Token::Bond));
+        assert_eq!(lexer.next(), Some(Token::Image));
+        assert_eq!(lexer.next(), Some(Token::SingleChoice));
+        assert_eq!(lexer.next(), Some(Token::MultipleChoice));
         assert_eq!(lexer.next(), Some(Token::Transfuse));
+        assert_eq!(lexer.next(), Some(Token::FieldSet));
         assert_eq!(lexer.next(), None);
     }
     #[test]
@@ -153,6 +202,16 @@ mod tests {
         assert_eq!(lexer.next(), Some(Token::Regex("[ab]+d?".to_owned())));
     }
     #[test]
+    fn test_str() {
+        let mut lexer = Token::lexer("#說明:\"一段 說明\" \"\"");
+        assert_eq!(lexer.next(), Some(Token::Sharp));
+        assert_eq!(lexer.next(), Some(Token::Identifier("說明".to_owned())));
+        assert_eq!(lexer.next(), Some(Token::Colon));
+        assert_eq!(lexer.next(), Some(Token::Str("一段 說明".to_owned())));
+        assert_eq!(lexer.next(), Some(Token::Str("".to_owned())));
+        assert_eq!(lexer.next(), None);
+    }
+    #[test]
     fn test_family() {
         let mut lexer = Token::lexer("@批踢踢文章");
         assert_eq!(lexer.next(), Some(Token::At));
@@ -1,6 +1,8 @@
 pub mod defs;
 pub mod error;
+pub mod format;
 pub mod instance_defs;
+pub mod json_schema;
 pub mod lexer;
 pub mod parser;
 pub mod validate;
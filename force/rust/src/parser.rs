@@ -1,4 +1,5 @@
 use crate::defs::*;
+use crate::format::format_category;
 use crate::lexer::{lexer, Token};
 use logos::Span;
 use regex::Regex;
@@ -71,6 +72,20 @@ impl Parser {
         }
         ret
     }
+    fn get_str(&mut self) -> ForceResult<String> {
+        let ret = if let Token::Str(s) = &self.cur {
+            Ok(s.clone())
+        } else {
+            Err(ForceError::NonExpect {
+                expect: Token::Str("某個字串".to_owned()),
+                fact: self.cur.clone(),
+            })
+        };
+        if let Ok(_) = ret {
+            self.advance();
+        }
+        ret
+    }
     fn parse_choice(&mut self) -> ForceResult<Choice> {
         match self.cur {
             Token::At => {
@@ -132,6 +147,46 @@ impl Parser {
             }),
         }
     }
+    // 鍵結對象之後的 (標籤, ...) ，可省略，其後接 ! 表示必須帶標籤
+    fn parse_bond_tags(&mut self) -> ForceResult<Option<BondTags>> {
+        if self.cur != Token::LeftParenthesis {
+            return Ok(None);
+        }
+        self.advance();
+        let mut tags = vec![self.get_identifier()?];
+        while let Token::Comma = self.cur {
+            self.advance();
+            tags.push(self.get_identifier()?);
+        }
+        self.eat(Token::RightParenthesis)?;
+        let required = self.cur == Token::Bang;
+        if required {
+            self.advance();
+        }
+        Ok(Some(BondTags { tags, required }))
+    }
+    // 投票選項 (甲, 乙, ...) ，其後可接 ~小時數 表示發文後多久截止
+    fn parse_poll(&mut self, multiple: bool) -> ForceResult<Poll> {
+        self.eat(Token::LeftParenthesis)?;
+        let mut options = vec![self.get_identifier()?];
+        while let Token::Comma = self.cur {
+            self.advance();
+            options.push(self.get_identifier()?);
+        }
+        self.eat(Token::RightParenthesis)?;
+        let close_hours = match self.cur {
+            Token::Tilde => {
+                self.advance();
+                Some(self.get_integer()?)
+            }
+            _ => None,
+        };
+        Ok(Poll {
+            options,
+            multiple,
+            close_hours,
+        })
+    }
     fn parse_datatype(&mut self) -> ForceResult<BasicDataType> {
         match self.cur {
             Token::Number => {
@@ -154,10 +209,20 @@ impl Parser {
                     _ => Ok(BasicDataType::Text(None)),
                 }
             }
+            Token::Image => {
+                self.advance();
+                Ok(BasicDataType::Image)
+            }
+            Token::SingleChoice | Token::MultipleChoice => {
+                let multiple = self.cur == Token::MultipleChoice;
+                self.advance();
+                Ok(BasicDataType::Poll(self.parse_poll(multiple)?))
+            }
             Token::Bond => {
                 self.advance();
                 let bondee = self.parse_bondee()?;
-                Ok(BasicDataType::Bond(bondee))
+                let tags = self.parse_bond_tags()?;
+                Ok(BasicDataType::Bond(bondee, tags))
             }
             _ => Err(ForceError::NoMeet {
                 expect: "型別".to_owned(),
@@ -213,17 +278,42 @@ impl Parser {
             }
             _ => DataType::Single(basic_datatype),
         };
-        Ok(Field { datatype, name })
+        let mut field = Field::new(datatype, name);
+        check_poll(&field)?;
+        self.parse_annotations(&mut field)?;
+        Ok(field)
     }
-    pub fn parse_category(&mut self) -> ForceResult<Category> {
-        let start = self.tokens[self.count].1.start;
-        // 讀取分類名稱
-        let name = self.get_identifier()?;
-
-        // 讀取分類族
-        let family = self.parse_family()?;
-
-        // 讀取各欄位資訊
+    // 欄位註記，形如 #說明:"..." #提示:"..." #預設:"..."，皆可省略
+    fn parse_annotations(&mut self, field: &mut Field) -> ForceResult<()> {
+        while let Token::Sharp = self.cur {
+            self.advance();
+            let key = self.get_identifier()?;
+            self.eat(Token::Colon)?;
+            match key.as_str() {
+                "說明" => field.description = Some(self.get_str()?),
+                "提示" => field.placeholder = Some(self.get_str()?),
+                "預設" => {
+                    let default = match self.cur.clone() {
+                        Token::Integer(n) => {
+                            self.advance();
+                            DefaultValue::Number(n as i64)
+                        }
+                        _ => DefaultValue::String(self.get_str()?),
+                    };
+                    check_default(field, &default)?;
+                    field.default = Some(default);
+                }
+                _ => {
+                    return Err(ForceError::NoMeet {
+                        expect: "說明、提示或預設".to_owned(),
+                        fact: Token::Identifier(key),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+    fn parse_fields(&mut self) -> ForceResult<Vec<Field>> {
         let mut fields = Vec::new();
         self.eat(Token::LeftCurlyBrace)?;
         loop {
@@ -233,25 +323,90 @@ impl Parser {
                 fields.push(self.parse_field()?);
             }
         }
-        let end = self.tokens[self.count].1.end;
         self.eat(Token::RightCurlyBrace)?;
-        Ok(Category {
+        Ok(fields)
+    }
+    // 分類名之後的 : 甲, 乙 ，可省略
+    fn parse_extends(&mut self) -> ForceResult<Vec<String>> {
+        let mut extends = vec![];
+        if let Token::Colon = self.cur {
+            self.advance();
+            extends.push(self.get_identifier()?);
+            while let Token::Comma = self.cur {
+                self.advance();
+                extends.push(self.get_identifier()?);
+            }
+        }
+        Ok(extends)
+    }
+    fn parse_category_definition(&mut self) -> ForceResult<(Category, Vec<String>)> {
+        let start = self.tokens[self.count].1.start;
+        // 讀取分類名稱
+        let name = self.get_identifier()?;
+
+        // 讀取繼承對象
+        let extends = self.parse_extends()?;
+
+        // 讀取分類族
+        let family = self.parse_family()?;
+
+        // 讀取各欄位資訊
+        let fields = self.parse_fields()?;
+        let end = self.tokens[self.count - 1].1.end;
+        let category = Category {
             name,
             fields,
             family,
             source: self.source[start..end].to_string(),
-        })
+        };
+        Ok((category, extends))
     }
-    fn parse_categories(&mut self) -> ForceResult<Categories> {
-        let mut categories = HashMap::new();
+    // 單獨解析一個分類時無從得知父分類的內容，故不允許繼承
+    pub fn parse_category(&mut self) -> ForceResult<Category> {
+        let (category, extends) = self.parse_category_definition()?;
+        match extends.into_iter().next() {
+            Some(parent) => Err(ForceError::InvalidExtend {
+                category: category.name,
+                parent,
+            }),
+            None => Ok(category),
+        }
+    }
+    // 依原始碼順序讀取所有頂層定義，不展開繼承
+    pub fn parse_definitions(&mut self) -> ForceResult<Vec<Definition>> {
+        let mut definitions = Vec::new();
         loop {
-            if let Token::End = self.cur {
-                break;
-            } else {
-                let category = Arc::new(self.parse_category()?);
-                categories.insert(category.name.clone(), category);
+            match self.cur {
+                Token::End => break,
+                Token::FieldSet => {
+                    self.advance();
+                    let name = self.get_identifier()?;
+                    let fields = self.parse_fields()?;
+                    definitions.push(Definition::FieldSet { name, fields });
+                }
+                Token::At => {
+                    self.advance();
+                    let family = self.get_identifier()?;
+                    let fields = self.parse_fields()?;
+                    definitions.push(Definition::FamilyFields { family, fields });
+                }
+                _ => {
+                    let (category, extends) = self.parse_category_definition()?;
+                    definitions.push(Definition::Category { category, extends });
+                }
             }
         }
+        Ok(definitions)
+    }
+    // 依原始碼順序讀取所有分類並展開繼承，不檢查撞名
+    pub fn parse_category_list(&mut self) -> ForceResult<Vec<Category>> {
+        resolve(self.parse_definitions()?)
+    }
+    fn parse_categories(&mut self) -> ForceResult<Categories> {
+        let mut categories = HashMap::new();
+        for category in self.parse_category_list()? {
+            categories.insert(category.name.clone(), Arc::new(category));
+        }
         return Ok(categories);
     }
     pub fn parse(&mut self) -> ForceResult<Force> {
@@ -280,7 +435,7 @@ impl Parser {
         for (_key, category) in &categories {
             for field in &category.fields {
                 match field.datatype.basic_type() {
-                    BasicDataType::Bond(bondee) => {
+                    BasicDataType::Bond(bondee, _) => {
                         if let Bondee::Choices {
                             family: family_choices,
                             category: category_choices,
@@ -317,6 +472,169 @@ impl Parser {
     }
 }
 
+fn push_inherited(inherited: &mut Vec<Field>, field: Field) {
+    if inherited.iter().all(|f| f.name != field.name) {
+        inherited.push(field);
+    }
+}
+
+struct Resolver<'a> {
+    categories: HashMap<&'a str, (&'a Category, &'a [String])>,
+    field_sets: HashMap<&'a str, &'a [Field]>,
+    family_fields: HashMap<&'a str, Vec<&'a Field>>,
+    // 正在展開的分類，用以偵測循環繼承
+    visiting: Vec<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    fn parent_fields(&mut self, category: &str, parent: &str) -> ForceResult<Vec<Field>> {
+        if let Some(fields) = self.field_sets.get(parent) {
+            return Ok(fields.to_vec());
+        }
+        match self.categories.get(parent) {
+            Some(&(parent, extends)) => self.resolve(parent, extends),
+            None => Err(ForceError::InvalidExtend {
+                category: category.to_owned(),
+                parent: parent.to_owned(),
+            }),
+        }
+    }
+    // 依序放入父分類（或欄位集）、分類族共用的欄位，同名者只取第一個；
+    // 分類自身的欄位會覆蓋同名的繼承欄位
+    fn resolve(
+        &mut self,
+        category: &'a Category,
+        extends: &'a [String],
+    ) -> ForceResult<Vec<Field>> {
+        if self.visiting.contains(&category.name.as_str()) {
+            return Err(ForceError::CyclicExtend {
+                category: category.name.clone(),
+            });
+        }
+        self.visiting.push(&category.name);
+        let mut inherited: Vec<Field> = Vec::new();
+        for parent in extends {
+            for field in self.parent_fields(&category.name, parent)? {
+                push_inherited(&mut inherited, field);
+            }
+        }
+        for family in &category.family {
+            if let Some(fields) = self.family_fields.get(family.as_str()) {
+                for field in fields {
+                    push_inherited(&mut inherited, (*field).clone());
+                }
+            }
+        }
+        let mut fields = inherited;
+        let inherited_count = fields.len();
+        for field in &category.fields {
+            match fields[..inherited_count]
+                .iter()
+                .position(|f| f.name == field.name)
+            {
+                Some(i) => fields[i] = field.clone(),
+                None => fields.push(field.clone()),
+            }
+        }
+        self.visiting.pop();
+        Ok(fields)
+    }
+}
+
+// 將繼承與共用欄位展開進 Category.fields ，展開過的分類重新產生 source ，使之能被單獨解析
+fn resolve(definitions: Vec<Definition>) -> ForceResult<Vec<Category>> {
+    let mut resolver = Resolver {
+        categories: HashMap::new(),
+        field_sets: HashMap::new(),
+        family_fields: HashMap::new(),
+        visiting: Vec::new(),
+    };
+    for definition in &definitions {
+        match definition {
+            Definition::Category { category, extends } => {
+                resolver
+                    .categories
+                    .entry(&category.name)
+                    .or_insert((category, extends));
+            }
+            Definition::FieldSet { name, fields } => {
+                resolver.field_sets.entry(name).or_insert(fields);
+            }
+            Definition::FamilyFields { family, fields } => {
+                resolver
+                    .family_fields
+                    .entry(family)
+                    .or_insert_with(Vec::new)
+                    .extend(fields);
+            }
+        }
+    }
+    let mut categories = Vec::new();
+    for definition in &definitions {
+        if let Definition::Category { category, extends } = definition {
+            let shares_family_fields = category
+                .family
+                .iter()
+                .any(|f| resolver.family_fields.contains_key(f.as_str()));
+            if extends.is_empty() && !shares_family_fields {
+                categories.push(category.clone());
+                continue;
+            }
+            let mut flattened = Category {
+                name: category.name.clone(),
+                family: category.family.clone(),
+                fields: resolver.resolve(category, extends)?,
+                source: String::new(),
+            };
+            flattened.source = format_category(&flattened);
+            categories.push(flattened);
+        }
+    }
+    Ok(categories)
+}
+
+fn check_poll(field: &Field) -> ForceResult<()> {
+    match &field.datatype {
+        DataType::Single(BasicDataType::Poll(poll)) => {
+            let mut options: Vec<&String> = poll.options.iter().collect();
+            options.sort();
+            options.dedup();
+            if options.len() == poll.options.len() {
+                return Ok(());
+            }
+        }
+        DataType::Optional(BasicDataType::Poll(_))
+        | DataType::Array {
+            t: BasicDataType::Poll(_),
+            ..
+        } => (),
+        _ => return Ok(()),
+    }
+    Err(ForceError::InvalidPoll {
+        field: field.name.clone(),
+    })
+}
+
+fn check_default(field: &Field, default: &DefaultValue) -> ForceResult<()> {
+    let ok = match (&field.datatype, default) {
+        (DataType::Array { .. }, _) => false,
+        (_, DefaultValue::Number(_)) => *field.datatype.basic_type() == BasicDataType::Number,
+        (_, DefaultValue::String(s)) => match field.datatype.basic_type() {
+            BasicDataType::OneLine => !s.contains('\n'),
+            BasicDataType::Text(None) => true,
+            BasicDataType::Text(Some(regex)) => regex.is_match(s),
+            _ => false,
+        },
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(ForceError::InvalidDefault {
+            field: field.name.clone(),
+        })
+    }
+}
+
 pub fn parse(source: &str) -> ForceResult<Force> {
     Parser::new(source).parse()
 }
@@ -338,14 +656,8 @@ mod test {
         let ans = Category {
             name: "新聞".to_owned(),
             fields: vec![
-                Field {
-                    datatype: BasicDataType::OneLine.into(),
-                    name: "記者".to_owned(),
-                },
-                Field {
-                    datatype: BasicDataType::OneLine.into(),
-                    name: "網址".to_owned(),
-                },
+                Field::new(BasicDataType::OneLine.into(), "記者".to_owned()),
+                Field::new(BasicDataType::OneLine.into(), "網址".to_owned()),
             ],
             family: vec!["轉載".to_owned(), "外部".to_owned()],
             source: source.to_owned(),
@@ -384,10 +696,10 @@ mod test {
 
         let ans = &Category {
             name: "作文比賽".to_owned(),
-            fields: vec![Field {
-                datatype: BasicDataType::Text(Some(Regex::new("(?s)我的志願是.+").unwrap())).into(),
-                name: "文章".to_owned(),
-            }],
+            fields: vec![Field::new(
+                BasicDataType::Text(Some(Regex::new("(?s)我的志願是.+").unwrap())).into(),
+                "文章".to_owned(),
+            )],
             family: vec![],
             source: source.to_owned(),
         };
@@ -400,14 +712,17 @@ mod test {
 
         let ans = &Category {
             name: "留言".to_owned(),
-            fields: vec![Field {
-                datatype: BasicDataType::Bond(Bondee::Choices {
-                    category: vec!["新聞".to_owned()],
-                    family: vec!["批踢踢文章".to_owned(), "狄卡文章".to_owned()],
-                })
+            fields: vec![Field::new(
+                BasicDataType::Bond(
+                    Bondee::Choices {
+                        category: vec!["新聞".to_owned()],
+                        family: vec!["批踢踢文章".to_owned(), "狄卡文章".to_owned()],
+                    },
+                    None,
+                )
                 .into(),
-                name: "原文".to_owned(),
-            }],
+                "原文".to_owned(),
+            )],
             family: vec![],
             source: source.to_owned(),
         };
@@ -454,4 +769,165 @@ mod test {
         );
         Ok(())
     }
+    #[test]
+    fn test_annotation() -> ForceResult<()> {
+        let source = "
+        測試 {
+            單行 標題 #說明:\"文章的標題\" #提示:\"請輸入標題\" #預設:\"無題\"
+            數字 數? #預設:3
+            文本 內文
+        }";
+        let category = parse_category(source)?;
+        let title = &category.fields[0];
+        assert_eq!(title.description, Some("文章的標題".to_owned()));
+        assert_eq!(title.placeholder, Some("請輸入標題".to_owned()));
+        assert_eq!(title.default, Some(DefaultValue::String("無題".to_owned())));
+        assert_eq!(category.fields[1].default, Some(DefaultValue::Number(3)));
+        assert_eq!(category.fields[2].default, None);
+
+        let mut data = serde_json::json!({ "標題": "有題", "內文": "" });
+        category.fill_defaults(&mut data);
+        assert_eq!(
+            data,
+            serde_json::json!({ "標題": "有題", "數": 3, "內文": "" })
+        );
+        // 必填欄位缺省時不補預設值，留待驗證失敗
+        let mut data = serde_json::json!({ "內文": "" });
+        category.fill_defaults(&mut data);
+        assert_eq!(
+            data,
+            serde_json::json!({ "標題": null, "數": 3, "內文": "" })
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_invalid_default() {
+        for source in &[
+            "測試 {單行 標題 #預設:3}",
+            "測試 {數字 數 #預設:\"三\"}",
+            "測試 {文本/^.{3}$/ 內文 #預設:\"12\"}",
+            "測試 {鍵結[*] 原文 #預設:1}",
+            "測試 {數字 數[1~2] #預設:1}",
+        ] {
+            match parse_category(source) {
+                Err(ForceError::InvalidDefault { field: _ }) => (),
+                res => panic!("{} => {:?}", source, res),
+            }
+        }
+        assert!(parse_category("測試 {單行 標題 #不存在:\"\"}").is_err());
+    }
+    #[test]
+    fn test_extends() -> ForceResult<()> {
+        let source = "
+        欄位集 出處 { 單行 來源 #提示:\"網址\" }
+        @轉載 { 單行 原作者 }
+        文章 : 出處 { 文本 內文 }
+        新聞 : 文章 @[轉載] { 單行 記者 文本/.{10,}/ 內文 }
+        短訊 @[轉載] {}";
+        let force = parse(source)?;
+        let names = |name: &str| -> Vec<String> {
+            let category = force.categories.get(name).unwrap();
+            category.fields.iter().map(|f| f.name.clone()).collect()
+        };
+        assert_eq!(names("文章"), vec!["來源", "內文"]);
+        // 自身欄位覆蓋同名的繼承欄位，但保留其位置
+        assert_eq!(names("新聞"), vec!["來源", "內文", "原作者", "記者"]);
+        assert_eq!(names("短訊"), vec!["原作者"]);
+
+        let news = force.categories.get("新聞").unwrap();
+        assert_eq!(
+            news.fields[1].datatype,
+            BasicDataType::Text(Some(Regex::new("(?s).{10,}").unwrap())).into()
+        );
+        // 展開後的原始碼可以單獨解析
+        let reparsed = parse_category(&news.source)?;
+        assert_eq!(reparsed.fields, news.fields);
+        assert_eq!(reparsed.family, news.family);
+        Ok(())
+    }
+    #[test]
+    fn test_invalid_extends() {
+        match parse("甲 : 乙 {}") {
+            Err(ForceError::InvalidExtend { category, parent }) => {
+                assert_eq!((category.as_str(), parent.as_str()), ("甲", "乙"))
+            }
+            res => panic!("{:?}", res),
+        }
+        match parse("甲 : 乙 {} 乙 : 甲 {}") {
+            Err(ForceError::CyclicExtend { category: _ }) => (),
+            res => panic!("{:?}", res),
+        }
+        assert!(parse_category("甲 : 乙 {}").is_err());
+    }
+    #[test]
+    fn test_bond_tags() -> ForceResult<()> {
+        let category =
+            parse_category("回應 { 鍵結[*](支持, 反對, 補充)! 原文 鍵結[*](引用) 參考? }")?;
+        assert_eq!(
+            category.fields[0].datatype,
+            BasicDataType::Bond(
+                Bondee::All,
+                Some(BondTags {
+                    tags: vec!["支持".to_owned(), "反對".to_owned(), "補充".to_owned()],
+                    required: true,
+                })
+            )
+            .into()
+        );
+        assert_eq!(
+            category.fields[1].datatype,
+            DataType::Optional(BasicDataType::Bond(
+                Bondee::All,
+                Some(BondTags {
+                    tags: vec!["引用".to_owned()],
+                    required: false,
+                })
+            ))
+        );
+        assert!(parse_category("回應 { 鍵結[*]() 原文 }").is_err());
+        Ok(())
+    }
+    #[test]
+    fn test_poll() -> ForceResult<()> {
+        let category = parse_category(
+            "提案 { 單選(贊成, 反對) 表決 複選(甲, 乙, 丙)~72 午餐 圖片 附圖[0~3] }",
+        )?;
+        assert_eq!(
+            category.fields[0].datatype,
+            BasicDataType::Poll(Poll {
+                options: vec!["贊成".to_owned(), "反對".to_owned()],
+                multiple: false,
+                close_hours: None,
+            })
+            .into()
+        );
+        assert_eq!(
+            category.fields[1].datatype,
+            BasicDataType::Poll(Poll {
+                options: vec!["甲".to_owned(), "乙".to_owned(), "丙".to_owned()],
+                multiple: true,
+                close_hours: Some(72),
+            })
+            .into()
+        );
+        assert_eq!(
+            category.fields[2].datatype,
+            DataType::Array {
+                t: BasicDataType::Image,
+                min: 0,
+                max: 3
+            }
+        );
+        for source in &[
+            "提案 { 單選(贊成, 贊成) 表決 }",
+            "提案 { 單選(贊成, 反對) 表決? }",
+            "提案 { 單選(贊成, 反對) 表決[1~2] }",
+        ] {
+            match parse_category(source) {
+                Err(ForceError::InvalidPoll { field: _ }) => (),
+                res => panic!("{} => {:?}", source, res),
+            }
+        }
+        Ok(())
+    }
 }
@@ -10,9 +10,13 @@ type Res<E> = Result<(), ValidationErrorCode<E>>;
 
 #[async_trait::async_trait]
 pub trait ValidatorTrait {
-    type OtherError;
+    type OtherError: Send;
     // XXX: 是否弄個 get_article 就好？
     async fn validate_bond(&self, bondee: &Bondee, data: &Bond) -> Result<(), Self::OtherError>;
+    // 檢查圖片是否存在等等，預設不檢查
+    async fn validate_image(&self, _image_id: i64) -> Result<(), Self::OtherError> {
+        Ok(())
+    }
     async fn validate_basic_datatype(
         &self,
         data_type: &BasicDataType,
@@ -41,70 +45,117 @@ pub trait ValidatorTrait {
                     ret!(RegexFail(regex.clone(), s.clone()))
                 }
             }
-            (BasicDataType::Bond(bondee), data) => {
+            (BasicDataType::Bond(bondee, tags), data) => {
                 let bond: Bond = match serde_json::from_value(data.clone()) {
                     Ok(b) => b,
                     Err(e) => {
                         ret!(Json(e));
                     }
                 };
-                // XXX: 檢查鍵能和標籤
+                if let Some(tags) = tags {
+                    match &bond.tag {
+                        None if tags.required => ret!(TagRequired),
+                        Some(tag) if !tags.tags.contains(tag) => ret!(TagNotAllowed(tag.clone())),
+                        _ => (),
+                    }
+                }
+                // XXX: 檢查鍵能
                 match self.validate_bond(bondee, &bond).await {
                     Ok(()) => (),
                     Err(e) => ret!(Other(e)),
                 }
             }
+            (BasicDataType::Image, Value::Number(n)) => match n.as_i64() {
+                Some(id) => {
+                    if let Err(e) = self.validate_image(id).await {
+                        ret!(Other(e));
+                    }
+                }
+                None => ret!(NotI64(n.clone())),
+            },
+            // 票數不在文章內容中
+            (BasicDataType::Poll(_), Value::Null) => (),
             _ => ret!(TypeMismatch(data_type.clone(), data.clone())),
         }
         Ok(())
     }
-    async fn validate_datatype(&self, data_type: &DataType, data: &Value) -> Res<Self::OtherError> {
+    async fn validate_datatype(
+        &self,
+        field_name: &str,
+        data_type: &DataType,
+        data: &Value,
+    ) -> Result<(), Vec<ValidationError<Self::OtherError>>> {
+        let mut errors = Vec::new();
+        macro_rules! push {
+            ($index:expr, $code:expr) => {
+                errors.push(ValidationError {
+                    field_name: field_name.to_owned(),
+                    index: $index,
+                    code: $code,
+                })
+            };
+        }
         match data_type {
             DataType::Optional(t) => {
                 if !data.is_null() {
-                    return self.validate_basic_datatype(t, data).await;
+                    if let Err(code) = self.validate_basic_datatype(t, data).await {
+                        push!(None, code);
+                    }
+                }
+            }
+            DataType::Single(t) => {
+                if let Err(code) = self.validate_basic_datatype(t, data).await {
+                    push!(None, code);
                 }
             }
-            DataType::Single(t) => return self.validate_basic_datatype(t, data).await,
             DataType::Array { t, min, max } => match data {
                 Value::Array(values) => {
                     if values.len() < *min || values.len() > *max {
-                        return Err(ArrayLengthMismatch {
-                            max: *max,
-                            min: *min,
-                            actual: values.len(),
-                        });
+                        push!(
+                            None,
+                            ArrayLengthMismatch {
+                                max: *max,
+                                min: *min,
+                                actual: values.len(),
+                            }
+                        );
                     }
-                    for value in values {
-                        self.validate_basic_datatype(t, value).await?;
+                    for (index, value) in values.iter().enumerate() {
+                        if let Err(code) = self.validate_basic_datatype(t, value).await {
+                            push!(Some(index), code);
+                        }
                     }
                 }
-                _ => return Err(NotArray(data.clone())),
+                _ => push!(None, NotArray(data.clone())),
             },
         };
-        Ok(())
+        if errors.len() > 0 {
+            Err(errors)
+        } else {
+            Ok(())
+        }
     }
+    /// 驗證所有欄位，一次回報全部錯誤
     async fn validate_category(
         &self,
         category: &Category,
         data: &Value,
-    ) -> Result<(), ValidationError<Self::OtherError>> {
+    ) -> Result<(), Vec<ValidationError<Self::OtherError>>> {
+        let mut errors = Vec::new();
         for field in &category.fields {
             log::trace!("驗證力語言欄位 {:?} => {:?}", field, data[&field.name]);
-            match self
-                .validate_datatype(&field.datatype, &data[&field.name])
+            if let Err(mut field_errors) = self
+                .validate_datatype(&field.name, &field.datatype, &data[&field.name])
                 .await
             {
-                Err(code) => {
-                    return Err(ValidationError {
-                        field_name: field.name.clone(),
-                        code,
-                    })
-                }
-                Ok(()) => (),
+                errors.append(&mut field_errors);
             }
         }
-        Ok(())
+        if errors.len() > 0 {
+            Err(errors)
+        } else {
+            Ok(())
+        }
     }
 }
 
@@ -131,13 +182,16 @@ mod tests {
         async fn validate(&self, category: &Category, data: &Value) -> bool {
             self.validate_category(category, data).await.is_ok()
         }
-        async fn err_tuple(
+        async fn err_tuples(
             &self,
             category: &Category,
             data: &Value,
-        ) -> (String, ValidationErrorCode<()>) {
-            let err = self.validate_category(category, data).await.unwrap_err();
-            (err.field_name, err.code)
+        ) -> Vec<(String, Option<usize>, ValidationErrorCode<()>)> {
+            let errors = self.validate_category(category, data).await.unwrap_err();
+            errors
+                .into_iter()
+                .map(|err| (err.field_name, err.index, err.code))
+                .collect()
         }
     }
     #[tokio::test]
@@ -151,8 +205,8 @@ mod tests {
         let data2 = json!({ "文字": &bad_string });
         assert!(Validator.validate(&category, &data1).await);
         assert_eq!(
-            Validator.err_tuple(&category, &data2).await,
-            ("文字".to_owned(), NotOneline(bad_string))
+            Validator.err_tuples(&category, &data2).await,
+            vec![("文字".to_owned(), None, NotOneline(bad_string))]
         );
         Ok(())
     }
@@ -244,4 +298,64 @@ mod tests {
         assert!(Validator.validate(&category, &data9).await == false);
         Ok(())
     }
+    #[tokio::test]
+    async fn test_collect_errors() -> ForceResult<()> {
+        let source = "測試 {單行 標題 數字 數 數字 陣列[1~2] 文本 內文}";
+        let category = parse_category(source)?;
+        let data = json!({
+            "標題": "a\nb",
+            "數": 1,
+            "陣列": [1, "2", 3, "4"],
+        });
+        let errors = Validator.err_tuples(&category, &data).await;
+        assert_eq!(errors.len(), 5);
+        assert_eq!(
+            errors[0],
+            ("標題".to_owned(), None, NotOneline("a\nb".to_owned()))
+        );
+        assert_eq!(
+            errors[1],
+            (
+                "陣列".to_owned(),
+                None,
+                ArrayLengthMismatch {
+                    min: 1,
+                    max: 2,
+                    actual: 4
+                }
+            )
+        );
+        assert_eq!(errors[2].1, Some(1));
+        assert_eq!(errors[3].1, Some(3));
+        assert_eq!(
+            errors[4],
+            (
+                "內文".to_owned(),
+                None,
+                TypeMismatch(BasicDataType::Text(None), Value::Null)
+            )
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_bond_tags() -> ForceResult<()> {
+        let category = parse_category("測試 {鍵結[*](支持, 反對)! 立場 鍵結[*](引用) 參考?}")?;
+        let bond = |tag: Option<&str>| json!({ "energy": 1, "target_article": 1, "tag": tag });
+        let data = json!({ "立場": bond(Some("支持")), "參考": bond(None) });
+        assert!(Validator.validate(&category, &data).await == true);
+        let data = json!({ "立場": bond(None), "參考": bond(Some("支持")) });
+        let errors = Validator.err_tuples(&category, &data).await;
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], (_, None, TagRequired)));
+        assert!(matches!(&errors[1].2, TagNotAllowed(tag) if tag == "支持"));
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_image() -> ForceResult<()> {
+        let category = parse_category("測試 {圖片 附圖[0~2]}")?;
+        assert!(Validator.validate(&category, &json!({ "附圖": [1, 2] })).await == true);
+        assert!(Validator.validate(&category, &json!({ "附圖": ["1"] })).await == false);
+        assert!(Validator.validate(&category, &json!({ "附圖": [1.5] })).await == false);
+        Ok(())
+    }
 }
@@ -13,9 +13,23 @@ export function show_bondee(bondee: Bondee): string {
 	throw 'impossible code';
 }
 
+// 鍵結欄位允許的標籤
+export type BondTags = {
+	tags: string[],
+	required: boolean,
+};
+
+// 投票欄位的定義，票數另存，文章內容中該欄位恆爲 null
+export type Poll = {
+	options: string[],
+	multiple: boolean,
+	close_hours: number | undefined,
+};
+
 export type BasicDataType = {
 	kind: 'bond',
-	bondee: Bondee
+	bondee: Bondee,
+	tags?: BondTags,
 } | {
 	kind: 'one_line'
 } | {
@@ -23,11 +37,19 @@ export type BasicDataType = {
 	regex: string | undefined
 } | {
 	kind: 'number'
+} | {
+	kind: 'image'
+} | {
+	kind: 'poll',
+	poll: Poll
 };
 
 export function show_basic_data_type(t: BasicDataType): string {
 	if (t.kind == 'bond') {
-		return `鍵結${show_bondee(t.bondee)}`;
+		if (t.tags == undefined) {
+			return `鍵結${show_bondee(t.bondee)}`;
+		}
+		return `鍵結${show_bondee(t.bondee)}(${t.tags.tags.join(', ')})${t.tags.required ? '!' : ''}`;
 	} else if (t.kind == 'one_line') {
 		return '單行';
 	} else if (t.kind == 'text') {
@@ -38,6 +60,11 @@ export function show_basic_data_type(t: BasicDataType): string {
 		}
 	} else if (t.kind == 'number') {
 		return '數字';
+	} else if (t.kind == 'image') {
+		return '圖片';
+	} else if (t.kind == 'poll') {
+		const close = t.poll.close_hours == undefined ? '' : `~${t.poll.close_hours}`;
+		return `${t.poll.multiple ? '複選' : '單選'}(${t.poll.options.join(', ')})${close}`;
 	}
 	throw 'impossible code';
 }
@@ -68,7 +95,11 @@ export function show_data_type(data_type: DataType): string {
 
 export interface Field {
 	datatype: DataType,
-	name: string
+	name: string,
+	// 以下註記皆可省略
+	description?: string,
+	placeholder?: string,
+	default?: number | string,
 }
 
 export interface Category {
@@ -77,6 +108,21 @@ export interface Category {
 	fields: Field[]
 }
 
+// 原始碼中的頂層定義，展開繼承與共用欄位之後才成爲 Category
+export type Definition = {
+	kind: 'category',
+	category: Category,
+	extends: string[],
+} | {
+	kind: 'field_set',
+	name: string,
+	fields: Field[],
+} | {
+	kind: 'family_fields',
+	family: string,
+	fields: Field[],
+};
+
 export type Categories = Map<string, Category>;
 
 export interface Force {
@@ -15,18 +15,29 @@ let lexer = moo.compile({
 	at: '@',
 	question_mark: '?',
 	tilde: '~',
+	left_parenthesis: '(',
+	right_parenthesis: ')',
+	bang: '!',
 
 	star: '*',
 
 	regex: new RegExp('/[^/]+/'),
 
+	// 字串，用於欄位的說明、提示與預設值
+	string: /"[^"]*"/,
+
 	identifier: {
-		match: /[^\s/\[\],\{\}\?~#@:]+/,
+		// ! 與 " 只在開頭時有特殊意義，不開頭的仍可用於識別子，以相容舊有的力
+		match: /[^\s/\[\],\{\}\(\)\?~!#@:"][^\s/\[\],\{\}\(\)\?~#@:]*/,
 		type: moo.keywords({
 			one_line: '單行',
 			text: '文本',
 			number: '數字',
 			bond: '鍵結',
+			image: '圖片',
+			single_choice: '單選',
+			multiple_choice: '複選',
+			field_set: '欄位集',
 
 			transfuse: '輸能',
 		})
@@ -36,7 +47,7 @@ let lexer = moo.compile({
 lexer.next = (next => () => {
 	let token;
 	while ((token = next.call(lexer)) && (token.type == 'whitespace' || token.type == 'new_line')) { }
-	if (token?.type == 'regex') { // 把正則表達式兩旁的 / / 拔掉
+	if (token?.type == 'regex' || token?.type == 'string') { // 把正則表達式兩旁的 / / 、字串兩旁的引號拔掉
 		token.value = token.value.slice(1, -1);
 	}
 	if (token?.type == 'identifier' && /^[0-9]+$/.test(token.value)) {
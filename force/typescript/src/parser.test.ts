@@ -70,4 +70,84 @@ test('解析分類族', () => {
 	expect(force.families.get('狄卡文章')!.length).toBe(1);
 	expect(force.families.get('狄卡文章')!.includes('有趣')).toBe(true);
 
-});
\ No newline at end of file
+});
+test('解析欄位註記', () => {
+	const source = '測試 { 單行 標題 #說明:"文章的標題" #提示:"請輸入標題" 數字 數? #預設:3 }';
+	const ans: Category = {
+		name: '測試',
+		fields: [
+			{
+				name: '標題',
+				datatype: { kind: 'single', t: { kind: 'one_line' } },
+				description: '文章的標題',
+				placeholder: '請輸入標題',
+			},
+			{
+				name: '數',
+				datatype: { kind: 'optional', t: { kind: 'number' } },
+				default: 3,
+			},
+		],
+		family: []
+	};
+	expect(parse_category(source)).toStrictEqual(ans);
+});
+
+test('預設值須與型別相符', () => {
+	for (const source of [
+		'測試 {單行 標題 #預設:3}',
+		'測試 {數字 數 #預設:"三"}',
+		'測試 {文本/^.{3}$/ 內文 #預設:"12"}',
+		'測試 {鍵結[*] 原文 #預設:1}',
+		'測試 {數字 數[1~2] #預設:1}',
+	]) {
+		expect(() => parse_category(source)).toThrow('預設值與型別不符');
+	}
+	expect(parse_category('測試 {文本/^.{3}$/ 內文 #預設:"123"}').fields[0].default).toBe('123');
+});
+
+test('展開繼承與共用欄位', () => {
+	const source = `
+		欄位集 出處 { 單行 來源 }
+		@轉載 { 單行 原作者 }
+		文章 : 出處 { 文本 內文 }
+		新聞 : 文章 @[轉載] { 單行 記者 文本/.{10,}/ 內文 }`;
+	const force = parse(source);
+	expect(force.categories.get('文章')!.fields.map(f => f.name)).toStrictEqual(['來源', '內文']);
+	expect(force.categories.get('新聞')!.fields.map(f => f.name)).toStrictEqual(['來源', '內文', '原作者', '記者']);
+	expect(() => parse('甲 : 乙 {}')).toThrow();
+	expect(() => parse('甲 : 乙 {} 乙 : 甲 {}')).toThrow();
+});
+
+test('解析鍵結標籤', () => {
+	const category = parse_category('回應 { 鍵結[*](支持, 反對)! 原文 }');
+	expect(category.fields[0].datatype).toStrictEqual({
+		kind: 'single',
+		t: {
+			kind: 'bond',
+			bondee: { kind: 'all' },
+			tags: { tags: ['支持', '反對'], required: true },
+		},
+	});
+});
+
+test('解析投票欄位', () => {
+	const category = parse_category('提案 { 複選(甲, 乙)~72 午餐 }');
+	expect(category.fields[0].datatype).toStrictEqual({
+		kind: 'single',
+		t: {
+			kind: 'poll',
+			poll: { options: ['甲', '乙'], multiple: true, close_hours: 72 },
+		},
+	});
+});
+
+test('解析圖片欄位', () => {
+	const category = parse_category('相簿 { 圖片 照片[1~9] }');
+	expect(category.fields[0].datatype).toStrictEqual({
+		kind: 'array',
+		t: { kind: 'image' },
+		min: 1,
+		max: 9,
+	});
+});
@@ -1,6 +1,6 @@
 import { lexer } from './lexer';
 import * as moo from 'moo';
-import { Bondee, BasicDataType, DataType, Category, Categories, Force, Field } from './defs';
+import { Bondee, BondTags, Poll, BasicDataType, DataType, Category, Categories, Definition, Force, Field } from './defs';
 
 function non_expect(expect: string, fact: moo.Token): Error {
 	return new Error(`預期 ${expect} ，但得到 ${JSON.stringify(fact)}`);
@@ -27,6 +27,27 @@ function invalid_bond(categories: string[], families: string[]): SemanticError {
 	return new SemanticError(msg);
 }
 
+// 預設值須與欄位型別相符，與 rust 版的 check_default 一致
+function check_default(field: Field, value: number | string): void {
+	const t = field.datatype.t;
+	const ok = (() => {
+		if (field.datatype.kind == 'array') {
+			return false;
+		} else if (typeof value == 'number') {
+			return t.kind == 'number';
+		} else if (t.kind == 'one_line') {
+			return value.search('\n') == -1;
+		} else if (t.kind == 'text') {
+			return t.regex == undefined || new RegExp(t.regex, 's').test(value);
+		} else {
+			return false;
+		}
+	})();
+	if (!ok) {
+		throw new SemanticError(`欄位 ${field.name} 的預設值與型別不符`);
+	}
+}
+
 type Choice = {
 	kind: 'category',
 	name: string,
@@ -91,6 +112,15 @@ export class Parser {
 			throw non_expect('integer', this.cur());
 		}
 	}
+	get_string(): string {
+		if (this.cur().type == 'string') {
+			const ret = this.cur().value;
+			this.advance();
+			return ret;
+		} else {
+			throw non_expect('字串', this.cur());
+		}
+	}
 	parse_choice(): Choice {
 		switch (this.cur().type) {
 			case 'at': {
@@ -153,6 +183,40 @@ export class Parser {
 			}
 		}
 	}
+	// 鍵結對象之後的 (標籤, ...) ，可省略，其後接 ! 表示必須帶標籤
+	parse_bond_tags(): BondTags | undefined {
+		if (this.cur().type != 'left_parenthesis') {
+			return undefined;
+		}
+		this.advance();
+		const tags = [this.get_identifier()];
+		while (this.cur().type == 'comma') {
+			this.advance();
+			tags.push(this.get_identifier());
+		}
+		this.eat('right_parenthesis');
+		const required = this.cur().type == 'bang';
+		if (required) {
+			this.advance();
+		}
+		return { tags, required };
+	}
+	// 投票選項 (甲, 乙, ...) ，其後可接 ~小時數 表示發文後多久截止
+	parse_poll(multiple: boolean): Poll {
+		this.eat('left_parenthesis');
+		const options = [this.get_identifier()];
+		while (this.cur().type == 'comma') {
+			this.advance();
+			options.push(this.get_identifier());
+		}
+		this.eat('right_parenthesis');
+		let close_hours = undefined;
+		if (this.cur().type == 'tilde') {
+			this.advance();
+			close_hours = this.get_integer();
+		}
+		return { options, multiple, close_hours };
+	}
 	parse_datatype(): BasicDataType {
 		switch (this.cur().type) {
 			case 'number': {
@@ -163,6 +227,10 @@ export class Parser {
 				this.advance();
 				return {kind: 'one_line'};
 			}
+			case 'image': {
+				this.advance();
+				return {kind: 'image'};
+			}
 			case 'text': {
 				this.advance();
 				if (this.cur().type == 'regex') {
@@ -179,12 +247,29 @@ export class Parser {
 					};
 				}
 			}
+			case 'single_choice':
+			case 'multiple_choice': {
+				const multiple = this.cur().type == 'multiple_choice';
+				this.advance();
+				return {
+					kind: 'poll',
+					poll: this.parse_poll(multiple)
+				};
+			}
 			case 'bond': {
 				this.advance();
 				const bondee = this.parse_bondee();
+				const tags = this.parse_bond_tags();
+				if (tags == undefined) {
+					return {
+						kind: 'bond',
+						bondee
+					};
+				}
 				return {
 					kind: 'bond',
-					bondee
+					bondee,
+					tags
 				};
 			}
 			default: {
@@ -238,19 +323,40 @@ export class Parser {
 				return {kind: 'single', t: basic_datatype};
 			}
 		})();
-		return { name, datatype };
+		const field: Field = { name, datatype };
+		this.parse_annotations(field);
+		return field;
 	}
-	parse_category(): Category {
-		// 讀取分類名稱
-		const name = this.get_identifier();
-
-		// 讀取分類族
-		const family = this.parse_family();
-
-		// 讀取各欄位資訊
+	// 欄位註記，形如 #說明:"..." #提示:"..." #預設:"..."，皆可省略
+	parse_annotations(field: Field): void {
+		while (!this.is_end() && this.cur().type == 'sharp') {
+			this.advance();
+			const key = this.get_identifier();
+			this.eat('colon');
+			switch (key) {
+				case '說明': {
+					field.description = this.get_string();
+					break;
+				}
+				case '提示': {
+					field.placeholder = this.get_string();
+					break;
+				}
+				case '預設': {
+					const value = this.cur().type == 'integer' ? this.get_integer() : this.get_string();
+					check_default(field, value);
+					field.default = value;
+					break;
+				}
+				default: {
+					throw new Error(`預期 說明、提示或預設 ，但得到 ${key}`);
+				}
+			}
+		}
+	}
+	parse_fields(): Field[] {
 		const fields = [];
 		this.eat('left_curly_brace');
-
 		while (true) {
 			if (this.cur().type == 'right_curly_brace') {
 				break;
@@ -259,21 +365,77 @@ export class Parser {
 			}
 		}
 		this.eat('right_curly_brace');
+		return fields;
+	}
+	// 分類名之後的 : 甲, 乙 ，可省略
+	parse_extends(): string[] {
+		const extends_: string[] = [];
+		if (this.cur().type == 'colon') {
+			this.advance();
+			extends_.push(this.get_identifier());
+			while (this.cur().type == 'comma') {
+				this.advance();
+				extends_.push(this.get_identifier());
+			}
+		}
+		return extends_;
+	}
+	parse_category_definition(): { category: Category, extends: string[] } {
+		// 讀取分類名稱
+		const name = this.get_identifier();
+
+		// 讀取繼承對象
+		const extends_ = this.parse_extends();
+
+		// 讀取分類族
+		const family = this.parse_family();
+
+		// 讀取各欄位資訊
+		const fields = this.parse_fields();
 		return {
-			name,
-			family,
-			fields
+			category: { name, family, fields },
+			extends: extends_,
 		};
 	}
+	// 單獨解析一個分類時無從得知父分類的內容，故不允許繼承
+	parse_category(): Category {
+		const { category, extends: extends_ } = this.parse_category_definition();
+		if (extends_.length > 0) {
+			throw new SemanticError(`分類 ${category.name} 繼承了不存在的分類或欄位集 ${extends_[0]}`);
+		}
+		return category;
+	}
+	// 依原始碼順序讀取所有頂層定義，不展開繼承
+	parse_definitions(): Definition[] {
+		const definitions: Definition[] = [];
+		while (!this.is_end()) {
+			switch (this.cur().type) {
+				case 'field_set': {
+					this.advance();
+					const name = this.get_identifier();
+					const fields = this.parse_fields();
+					definitions.push({ kind: 'field_set', name, fields });
+					break;
+				}
+				case 'at': {
+					this.advance();
+					const family = this.get_identifier();
+					const fields = this.parse_fields();
+					definitions.push({ kind: 'family_fields', family, fields });
+					break;
+				}
+				default: {
+					const { category, extends: extends_ } = this.parse_category_definition();
+					definitions.push({ kind: 'category', category, extends: extends_ });
+				}
+			}
+		}
+		return definitions;
+	}
 	parse_categories(): Categories {
 		let categories = new Map<string, Category>();
-		while (true) {
-			if (this.is_end()) {
-				break;
-			} else {
-				const category = this.parse_category();
-				categories.set(category.name, category);
-			}
+		for (let category of resolve(this.parse_definitions())) {
+			categories.set(category.name, category);
 		}
 		return categories;
 	}
@@ -329,6 +491,90 @@ export class Parser {
 	}
 }
 
+function push_inherited(inherited: Field[], field: Field): void {
+	if (inherited.every(f => f.name != field.name)) {
+		inherited.push(field);
+	}
+}
+
+// 將繼承與共用欄位展開進 Category.fields
+function resolve(definitions: Definition[]): Category[] {
+	const categories = new Map<string, { category: Category, extends: string[] }>();
+	const field_sets = new Map<string, Field[]>();
+	const family_fields = new Map<string, Field[]>();
+	for (let definition of definitions) {
+		if (definition.kind == 'category') {
+			if (!categories.has(definition.category.name)) {
+				categories.set(definition.category.name, definition);
+			}
+		} else if (definition.kind == 'field_set') {
+			if (!field_sets.has(definition.name)) {
+				field_sets.set(definition.name, definition.fields);
+			}
+		} else {
+			const fields = family_fields.get(definition.family) ?? [];
+			family_fields.set(definition.family, [...fields, ...definition.fields]);
+		}
+	}
+
+	// 正在展開的分類，用以偵測循環繼承
+	const visiting: string[] = [];
+	function parent_fields(category: string, parent: string): Field[] {
+		const field_set = field_sets.get(parent);
+		if (field_set != undefined) {
+			return field_set;
+		}
+		const def = categories.get(parent);
+		if (def == undefined) {
+			throw new SemanticError(`分類 ${category} 繼承了不存在的分類或欄位集 ${parent}`);
+		}
+		return resolve_fields(def.category, def.extends);
+	}
+	// 依序放入父分類（或欄位集）、分類族共用的欄位，同名者只取第一個；
+	// 分類自身的欄位會覆蓋同名的繼承欄位
+	function resolve_fields(category: Category, extends_: string[]): Field[] {
+		if (visiting.includes(category.name)) {
+			throw new SemanticError(`分類 ${category.name} 的繼承關係形成循環`);
+		}
+		visiting.push(category.name);
+		const fields: Field[] = [];
+		for (let parent of extends_) {
+			for (let field of parent_fields(category.name, parent)) {
+				push_inherited(fields, field);
+			}
+		}
+		for (let family of category.family) {
+			for (let field of family_fields.get(family) ?? []) {
+				push_inherited(fields, field);
+			}
+		}
+		const inherited_count = fields.length;
+		for (let field of category.fields) {
+			const i = fields.slice(0, inherited_count).findIndex(f => f.name == field.name);
+			if (i >= 0) {
+				fields[i] = field;
+			} else {
+				fields.push(field);
+			}
+		}
+		visiting.pop();
+		return fields;
+	}
+
+	const ret: Category[] = [];
+	for (let definition of definitions) {
+		if (definition.kind == 'category') {
+			const { category, extends: extends_ } = definition;
+			ret.push({
+				name: category.name,
+				family: category.family,
+				fields: resolve_fields(category, extends_),
+			});
+		}
+	}
+	return ret;
+}
+
 export function parse(source: string): Force {
 	const parser = new Parser(source);
 	return parser.parse();
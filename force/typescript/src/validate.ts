@@ -27,7 +27,8 @@ export abstract class ValidatorTrait {
 		}
 	}
 	async validate_basic_datatype(datatype: BasicDataType, data: any): Promise<string | undefined> {
-		if (datatype.kind == 'number') {
+		if (datatype.kind == 'number' || datatype.kind == 'image') {
+			// 圖片欄位的值爲圖片 id ，是否存在由後端檢查
 			return (await this.validate_number(data));
 		} else if (datatype.kind == 'one_line' && typeof data == 'string') {
 			if (data.search('\n') == -1) {
@@ -45,6 +46,9 @@ export abstract class ValidatorTrait {
 			}
 		} else if (datatype.kind == 'bond') {
 			return (await this.validate_bondee(datatype.bondee, data));
+		} else if (datatype.kind == 'poll' && data == null) {
+			// 票數不在文章內容中
+			return undefined;
 		} else {
 			return VALIDATE_INFO.JSON_TYPE_MISMATCH;
 		}
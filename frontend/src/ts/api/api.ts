@@ -1,22 +1,43 @@
 import * as api_trait from './api_trait';
 import { toastErr } from '../../tsx/utils';
 
-export class ApiFetcher extends api_trait.RootQueryFetcher {
-	async fetchResult(query: Object): Promise<string> {
-		let info = JSON.stringify(query);
-		if (info.length > 300) {
-			info = '';
-		}
-		const url = `http://${window.location.hostname}:${window.location.port}/api?query=${info}`;
+async function post(body: Object): Promise<string> {
+	let info = JSON.stringify(body);
+	if (info.length > 300) {
+		info = '';
+	}
+	const url = `http://${window.location.hostname}:${window.location.port}/api?query=${info}`;
 
-		const response = await fetch(url, {
-			body: JSON.stringify(query),
-			method: 'POST'
-		});
+	const response = await fetch(url, {
+		body: JSON.stringify(body),
+		method: 'POST'
+	});
 
-		const text = await response.text();
+	const text = await response.text();
 
-		return (text);
+	return (text);
+}
+
+export class ApiFetcher extends api_trait.RootQueryFetcher {
+	async fetchResult(query: Object): Promise<string> {
+		return post(query);
+	}
+}
+
+// 需要合併請求時才選用，批次內的請求在伺服器端並行執行
+export class BatchedApiFetcher extends api_trait.BatchedRootQueryFetcher {
+	async fetchBatch(queries: Object[]): Promise<string[]> {
+		// 只有一個請求就照常送出，不包成陣列
+		if (queries.length == 1) {
+			return [await post(queries[0])];
+		}
+		const text = await post(queries);
+		const results = JSON.parse(text);
+		if (!Array.isArray(results)) {
+			// 整批失敗，每個呼叫都拿到同樣的錯誤
+			return queries.map(() => text);
+		}
+		return results.map((result: Object) => JSON.stringify(result));
 	}
 }
 
@@ -54,4 +75,5 @@ export function unwrap_or<T, E>(result: api_trait.Result<T, E>, alt: T): T {
 	}
 }
 
-export const API_FETCHER = new ApiFetcher();
\ No newline at end of file
+export const API_FETCHER = new ApiFetcher();
+export const API_BATCH_FETCHER = new BatchedApiFetcher();
\ No newline at end of file
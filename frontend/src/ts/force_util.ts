@@ -21,12 +21,14 @@ export function get_non_family_members(force: Force, family: string): string[] {
 	return non_members;
 }
 
-// 創造一個符合力語言型別的空實例
+// 創造一個符合力語言型別的空實例，有預設值的欄位填入預設值
 export function new_content(category: Category): { [index: string]: string | string[] } {
 	let content: { [index: string]: string | string[] } = {};
 	for (let field of category.fields) {
 		if (field.datatype.kind == 'array') {
 			content[field.name] = [];
+		} else if (field.default != undefined) {
+			content[field.name] = field.default.toString();
 		} else {
 			content[field.name] = '';
 		}
@@ -2,7 +2,8 @@ import * as React from 'react';
 import { Link } from 'react-router-dom';
 
 import { API_FETCHER, unwrap_or } from '../ts/api/api';
-import { Board, BoardType } from '../ts/api/api_trait';
+import { Board, BoardType, ArticleMeta, HotWindow } from '../ts/api/api_trait';
+import { ArticleCard } from './article_card';
 
 import style from '../css/board_list.module.css';
 import '../css/layout.css';
@@ -23,6 +24,29 @@ function BoardBlock(props: { board: { board_name: string, board_type: string, ti
 	</Link>;
 }
 
+function HotArticles(): JSX.Element {
+	let [hot_window, setHotWindow] = React.useState<HotWindow>(HotWindow.Today);
+	let [articles, setArticles] = React.useState<ArticleMeta[]>([]);
+	React.useEffect(() => {
+		API_FETCHER.queryHotArticles(null, hot_window).then(res => {
+			setArticles(unwrap_or(res, []));
+		});
+	}, [hot_window]);
+
+	return <div className={style.hotArticles}>
+		<div className={style.windows}>
+			<span className={hot_window == HotWindow.Now ? style.selected : ''} onClick={() => setHotWindow(HotWindow.Now)}>即時</span>
+			<span className={hot_window == HotWindow.Today ? style.selected : ''} onClick={() => setHotWindow(HotWindow.Today)}>今日</span>
+			<span className={hot_window == HotWindow.Week ? style.selected : ''} onClick={() => setHotWindow(HotWindow.Week)}>本週</span>
+		</div>
+		{
+			articles.slice(0, 10).map(article => (
+				<ArticleCard article={article} key={article.id} />
+			))
+		}
+	</div>;
+}
+
 function BoardList(): JSX.Element {
 	let [board_list, setBoardList] = React.useState<Board[]>([]);
 	React.useEffect(() => {
@@ -33,6 +57,7 @@ function BoardList(): JSX.Element {
 
 	return <div className={style.boardList}>
 		<div className="mainContent">
+			<HotArticles />
 			{
 				board_list.map(board => (
 					<div className={style.boardBlock} key={board.id}>
@@ -4,8 +4,8 @@ import { MainScrollState } from '../global_state/main_scroll';
 import { API_FETCHER, unwrap } from '../../ts/api/api';
 import { ArticleHeader, ArticleLine, ArticleFooter, SimpleArticleCardById } from '../article_card';
 import style from '../../css/board_switch/article_page.module.css';
-import { Article, Board, Bond } from '../../ts/api/api_trait';
-import { parse_category, Field } from '../../../../force/typescript/index';
+import { Article, Board, Bond, ReportReason } from '../../ts/api/api_trait';
+import { parse_category, Field, Poll } from '../../../../force/typescript/index';
 import { isImageLink, isLink } from '../../ts/regex_util';
 import { toastErr } from '../utils';
 import { BonderCards, ReplyButtons, SatelliteButtons, SatelliteCards } from '../article_card/bonder';
@@ -82,6 +82,8 @@ function ShowSingleField(props: { field: Field, value: any }): JSX.Element {
 		return <div className={style.cardWrap}>
 			<SimpleArticleCardById article_id={bond.target_article} />
 		</div>;
+	} else if (field.datatype.t.kind == 'image') {
+		return <img className={style.image} src={`/image/${value}`} />;
 	} else {
 		return <ShowText text={`${value}`} />;
 	}
@@ -101,6 +103,46 @@ function ShowArrayField(props: { field: Field, value: any[] }): JSX.Element {
 	return <>{ret}</>;
 }
 
+function ShowPoll(props: { article: Article, field: Field, poll: Poll }): JSX.Element {
+	const { article, field, poll } = props;
+	const [tallies, setTallies] = React.useState(
+		article.meta.stat.polls.filter(t => t.field_name == field.name)
+	);
+	const [choices, setChoices] = React.useState(
+		article.meta.personal_meta.votes.find(v => v.field_name == field.name)?.choices ?? []
+	);
+
+	function vote(option: string): void {
+		let next: string[];
+		if (choices.includes(option)) {
+			next = choices.filter(c => c != option);
+		} else if (poll.multiple) {
+			next = [...choices, option];
+		} else {
+			next = [option];
+		}
+		API_FETCHER.castVote(article.meta.id, field.name, next)
+			.then(data => unwrap(data))
+			.then(tallies => {
+				setTallies(tallies);
+				setChoices(next);
+			})
+			.catch(err => toastErr(err));
+	}
+
+	return <div className={style.poll}>
+		{
+			poll.options.map(option => {
+				const count = tallies.find(t => t.choice == option)?.count ?? 0;
+				return <div key={option} className={style.pollOption} onClick={() => vote(option)}>
+					<span>{choices.includes(option) ? '☑' : '☐'} {option}</span>
+					<span>{count} 票</span>
+				</div>;
+			})
+		}
+	</div>;
+}
+
 export function ArticleContent(props: { article: Article }): JSX.Element {
 	const article = props.article;
 	const category = parse_category(article.meta.category_source);
@@ -114,7 +156,9 @@ export function ArticleContent(props: { article: Article }): JSX.Element {
 					{
 						(() => {
 							const value = content[field.name];
-							if (field.datatype.kind == 'array') {
+							if (field.datatype.t.kind == 'poll') {
+								return <ShowPoll key={field.name} article={article} field={field} poll={field.datatype.t.poll} />;
+							} else if (field.datatype.kind == 'array') {
 								// @ts-ignore
 								return <ShowArrayField key={field.name} field={field} value={value} />;
 							} else {
@@ -138,6 +182,8 @@ function ArticleDisplayPage(props: { article: Article, board: Board }): JSX.Elem
 	const category_name = article.meta.category_name;
 
 	return <div className={style.articlePage}>
+		<ModerationButtons article={article} />
+		<ReportForm article={article} />
 		<ArticleHeader
 			user_name={article.meta.author_name}
 			board_name={article.meta.board_name}
@@ -147,7 +193,7 @@ function ArticleDisplayPage(props: { article: Article, board: Board }): JSX.Elem
 			id={article.meta.id}
 			category_name={category_name}
 			title={article.meta.title} />
-		<ReplyButtons article={article.meta} board={board} />
+		{article.meta.locked ? <div className={style.locked}>本文已鎖定，無法回覆</div> : <ReplyButtons article={article.meta} board={board} />}
 		<ArticleContent article={article} />
 		<ArticleFooter  article={article.meta}/>
 		<ReplyList article={article} />
@@ -155,6 +201,104 @@ function ArticleDisplayPage(props: { article: Article, board: Board }): JSX.Elem
 	</div>;
 }
 
+const REPORT_REASONS: [ReportReason, string][] = [
+	[ReportReason.Spam, '垃圾訊息'],
+	[ReportReason.Abuse, '人身攻擊'],
+	[ReportReason.OffTopic, '離題'],
+	[ReportReason.Other, '其它'],
+];
+
+function ReportForm(props: { article: Article }): JSX.Element {
+	const [open, setOpen] = React.useState(false);
+	const [reason, setReason] = React.useState(ReportReason.Spam);
+	const [detail, setDetail] = React.useState('');
+	if (!open) {
+		return <div className={style.moderation}>
+			<button onClick={() => setOpen(true)}>檢舉</button>
+		</div>;
+	}
+	async function submit(): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.reportArticle(props.article.meta.id, reason, detail == '' ? null : detail));
+			setOpen(false);
+			setDetail('');
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	return <div className={style.moderation}>
+		<select value={reason} onChange={evt => setReason(evt.target.value as ReportReason)}>
+			{REPORT_REASONS.map(([r, txt]) => <option key={r} value={r}>{txt}</option>)}
+		</select>
+		<input placeholder="補充說明（選填）" value={detail} onChange={evt => setDetail(evt.target.value)} />
+		<button onClick={submit}>送出</button>
+		<button onClick={() => setOpen(false)}>取消</button>
+	</div>;
+}
+
+// 僅執政黨成員看得到
+function ModerationButtons(props: { article: Article }): JSX.Element {
+	const { meta } = props.article;
+	const [is_moderator, setIsModerator] = React.useState(false);
+	const [locked, setLocked] = React.useState(meta.locked);
+	const [pinned, setPinned] = React.useState(meta.pinned);
+	const [featured, setFeatured] = React.useState(meta.featured);
+	React.useEffect(() => {
+		API_FETCHER.queryIsModerator(meta.board_id)
+			.then(data => setIsModerator(unwrap(data)))
+			.catch(err => toastErr(err));
+	}, [meta.board_id]);
+	if (!is_moderator) {
+		return <></>;
+	}
+	async function hide(): Promise<void> {
+		const reason = window.prompt('隱藏理由');
+		if (reason == null) {
+			return;
+		}
+		try {
+			unwrap(await API_FETCHER.hideArticle(meta.id, true, reason));
+			window.location.reload();
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	async function toggleLock(): Promise<void> {
+		const reason = window.prompt(locked ? '解鎖理由' : '鎖定理由');
+		if (reason == null) {
+			return;
+		}
+		try {
+			unwrap(await API_FETCHER.lockArticle(meta.id, !locked, reason));
+			setLocked(!locked);
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	async function togglePin(): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.pinArticle(meta.id, !pinned));
+			setPinned(!pinned);
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	async function toggleFeature(): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.featureArticle(meta.id, !featured));
+			setFeatured(!featured);
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	return <div className={style.moderation}>
+		<button onClick={hide}>隱藏</button>
+		<button onClick={toggleLock}>{locked ? '解鎖' : '鎖定'}</button>
+		<button onClick={togglePin}>{pinned ? '取消置頂' : '置頂'}</button>
+		<button onClick={toggleFeature}>{featured ? '取消精華' : '精華'}</button>
+	</div>;
+}
+
 type Props = RouteComponentProps<{ article_id?: string, board_name?: string }> & {
 	board: Board
 };
@@ -28,7 +28,8 @@ async function fetchArticles(
 ): Promise<ArticleMeta[]> {
 	let articles = unwrap_or(await API_FETCHER.queryArticleList(page_size, min_id, null,
 		board_name, { BlackList: [force_util.SATELLITE] }), []);
-	let new_min = Math.min(...articles.map(a => a.id));
+	// 置頂文章不在時間序內，不能拿來決定下一頁的起點
+	let new_min = Math.min(...articles.filter(a => !a.pinned).map(a => a.id));
 	if (min_id != null) {
 		new_min = Math.min(min_id, new_min);
 	}
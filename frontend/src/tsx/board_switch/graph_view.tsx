@@ -198,7 +198,7 @@ export function GraphViewInner(props: { meta: ArticleMeta, panel: Panel } & Rout
 	}
 
 	React.useEffect(() => {
-		API_FETCHER.queryGraph(props.meta.id, null, { BlackList: [force_util.SATELLITE] }).then(res => {
+		API_FETCHER.queryGraph(props.meta.id, null, null, { BlackList: [force_util.SATELLITE] }).then(res => {
 			let g = unwrap(res);
 			let counter = new LinkNumCounter();
 			let nodes = g.nodes.map(n => {
@@ -13,6 +13,8 @@ import '../../css/layout.css';
 import style from '../../css/board_switch/board_page.module.css';
 import { toastErr } from '../utils';
 
+const HEARTBEAT_INTERVAL = 30 * 1000;
+
 type RenderHeader = { render_header: (board: Board, url: string, subscribe_count: number) => JSX.Element };
 
 function BoardSwitch(props: { board_name: string, board_type: BoardType, hide_sidebar?: boolean, history: History } &RenderHeader): JSX.Element {
@@ -41,6 +43,17 @@ function BoardSwitch(props: { board_name: string, board_type: BoardType, hide_si
 			setFetching(false);
 		});
 	}, [board_name, board_type]);
+	// 後端的在場紀錄一分鐘後失效，停留在看板上就得持續續期
+	const board_id = board ? board.id : null;
+	React.useEffect(() => {
+		if (board_id == null) {
+			return;
+		}
+		const timer = setInterval(() => {
+			API_FETCHER.boardHeartbeat(board_id).catch(err => console.warn(err));
+		}, HEARTBEAT_INTERVAL);
+		return () => clearInterval(timer);
+	}, [board_id]);
 	if (fetching) {
 		return <></>;
 	} else if (board == null) {
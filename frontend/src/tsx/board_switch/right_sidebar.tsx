@@ -2,7 +2,7 @@ import * as React from 'react';
 import { RouteComponentProps } from 'react-router';
 import { UserState } from '../global_state/user';
 import { EditorPanelState } from '../global_state/editor_panel';
-import { Board, Party } from '../../ts/api/api_trait';
+import { Board, Party, Election } from '../../ts/api/api_trait';
 import { API_FETCHER, unwrap } from '../../ts/api/api';
 
 import style from '../../css/board_switch/right_sidebar.module.css';
@@ -19,7 +19,8 @@ export function BoardSidebar(props: Props): JSX.Element {
 	let { has_subscribed, toggleSubscribe } = useSubscribeBoard(props.board);
 
 	React.useEffect(() => {
-		API_FETCHER.queryBoardPartyList(props.board.id).then(res => {
+		// 在野黨依鍵能排序
+		API_FETCHER.queryPartyEnergyRanking(props.board.id).then(res => {
 			setParties(unwrap(res));
 		}).catch(err => toastErr(err));
 	}, [props.board.id]);
@@ -62,9 +63,7 @@ export function BoardSidebar(props: Props): JSX.Element {
 		<div className={style.rightSidebarItem}>
 			<div className={style.rightSidebarBlock}>
 				<div className={style.header}>看板簡介</div>
-				<div className={style.content}>
-					{props.board.detail}
-				</div>
+				<BoardDetail board={props.board} />
 				{/* <div className={style.rightSidebarButton trackBoardButton}>訂閱此看板</div> */}
 			</div>
 		</div>
@@ -75,9 +74,46 @@ export function BoardSidebar(props: Props): JSX.Element {
 				<PartyList parties={parties}/>
 			</div>
 		</div>
+
+		<ElectionBlock board={props.board} parties={parties} />
 	</>;
 }
 
+// 執政黨成員可直接修改看板簡介
+function BoardDetail(props: { board: Board }): JSX.Element {
+	const [detail, setDetail] = React.useState(props.board.detail);
+	const [draft, setDraft] = React.useState<string | null>(null);
+	const [is_moderator, setIsModerator] = React.useState(false);
+	React.useEffect(() => {
+		setDetail(props.board.detail);
+		API_FETCHER.queryIsModerator(props.board.id)
+			.then(res => setIsModerator(unwrap(res)))
+			.catch(err => toastErr(err));
+	}, [props.board]);
+
+	async function save(): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.updateBoard(props.board.id, null, draft, null));
+			setDetail(draft!);
+			setDraft(null);
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+
+	if (draft != null) {
+		return <div className={style.content}>
+			<textarea value={draft} onChange={evt => setDraft(evt.target.value)} />
+			<button onClick={save}>儲存</button>
+			<button onClick={() => setDraft(null)}>取消</button>
+		</div>;
+	}
+	return <div className={style.content}>
+		{detail}
+		{is_moderator && <div className={style.rightSidebarButton} onClick={() => setDraft(detail)}>編輯簡介</div>}
+	</div>;
+}
+
 function PartyList(props: {parties: Party[]}): JSX.Element {
 	let oppositions = new Array<Party>();
 	let ruling: Party | null = null;
@@ -114,6 +150,76 @@ function PartyList(props: {parties: Party[]}): JSX.Element {
 	</>;
 }
 
+function ElectionBlock(props: { board: Board, parties: Party[] }): JSX.Element {
+	const { user_state } = UserState.useContainer();
+	const [election, setElection] = React.useState<Election | null>(null);
+	const board_id = props.board.id;
+
+	const refresh = React.useCallback(() => {
+		API_FETCHER.queryElection(board_id)
+			.then(res => setElection(unwrap(res)))
+			.catch(err => toastErr(err));
+	}, [board_id]);
+	React.useEffect(refresh, [refresh]);
+
+	if (election == null) {
+		return <></>;
+	}
+	const e = election;
+	const open = !e.tallied && new Date(e.end_time) > new Date();
+
+	async function vote(party_id: number): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.voteElection(e.id, party_id));
+			refresh();
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	async function register(party_id: number): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.registerCandidate(e.id, party_id));
+			refresh();
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+	// 由自己擔任黨主席、尚未參選的政黨
+	const registrable = open && user_state.login ? props.parties.filter(p =>
+		p.chairman_id == user_state.id && !e.candidates.some(c => c.party_id == p.id)) : [];
+
+	return <div className={style.rightSidebarItem}>
+		<div className={style.rightSidebarBlock}>
+			<div className={style.header}>{open ? '選舉進行中' : '上次選舉'}</div>
+			<div className={style.content}>
+				<div>{open ? '投票截止' : '開票時間'}：{new Date(e.end_time).toLocaleString()}</div>
+				{
+					e.candidates.map(c => <div key={c.party_id} className={style.partyItem}>
+						<div className={style.partyName}>
+							{c.party_name}
+							{e.winner_party_id == c.party_id ? ' 🏆' : ''}
+						</div>
+						<div className={style.partyScore}>
+							{
+								c.votes != null ? `${c.votes} 票` :
+									(open && user_state.login ?
+										<button onClick={() => vote(c.party_id)} disabled={e.my_vote == c.party_id}>
+											{e.my_vote == c.party_id ? '已投' : '投票'}
+										</button> : null)
+							}
+						</div>
+					</div>)
+				}
+				{
+					registrable.map(p => <div key={p.id} className={style.rightSidebarButton} onClick={() => register(p.id)}>
+						以{p.party_name}登記參選
+					</div>)
+				}
+			</div>
+		</div>
+	</div>;
+}
+
 export function ArticleSidebar(): JSX.Element {
 	return <>
 		<div className={style.rightSidebarItem}>
@@ -4,15 +4,14 @@ import { Link } from 'react-router-dom';
 import { API_FETCHER, unwrap_or } from '../ts/api/api';
 import { UserState } from './global_state/user';
 import { STORAGE_NAME } from '../ts/constants';
-import { BoardOverview } from '../ts/api/api_trait';
+import { BoardOverview, HotWindow } from '../ts/api/api_trait';
 
 import style from '../css/browsebar.module.css';
 import { SubscribedBoardsState } from './global_state/subscribed_boards';
 
 async function fetchHotBoards(): Promise<BoardOverview[]> {
-	let boards = unwrap_or(await API_FETCHER.queryHotBoards(), []);
-	boards.sort((b1, b2) => b2.popularity - b1.popularity);
-	return boards;
+	// 後端已依熱度排序
+	return unwrap_or(await API_FETCHER.queryHotBoards(HotWindow.Now), []);
 }
 
 // TODO: 應該用 context 記住熱門看板與訂閱看板，以免次切換測邊欄都要向後端發 request
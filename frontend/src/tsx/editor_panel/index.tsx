@@ -74,6 +74,29 @@ function EditorPanel(): JSX.Element | null {
 	}
 }
 
+// 選取圖片後立即上傳，回傳圖片 id
+function ImageUpload(props: { onUploaded: (id: string) => void }): JSX.Element {
+	const onChange = (evt: React.ChangeEvent<HTMLInputElement>): void => {
+		const file = evt.target.files?.[0];
+		if (file == undefined) {
+			return;
+		}
+		const reader = new FileReader();
+		reader.onloadend = async () => {
+			try {
+				// 因爲使用 readAsDataURL ，故 result 爲字串
+				const data = (reader.result as string).split(',')[1];
+				const id = unwrap(await API_FETCHER.uploadImage(data));
+				props.onUploaded(`${id}`);
+			} catch (err) {
+				toastErr(err);
+			}
+		};
+		reader.readAsDataURL(file);
+	};
+	return <input type="file" accept="image/*" onChange={onChange} />;
+}
+
 const SingleField = (props: { field: Force.Field, validator: Validator }): JSX.Element => {
 	const { field, validator } = props;
 	const [validate_info, setValidateInfo] = useState<undefined | string>(undefined);
@@ -89,7 +112,7 @@ const SingleField = (props: { field: Force.Field, validator: Validator }): JSX.E
 	if (editor_panel_data == null) { return <></>; }
 
 	const input_props = {
-		placeholder: field.name,
+		placeholder: field.placeholder ?? field.name,
 		id: field.name,
 		value: content[field.name],
 		onChange: (evt: { target: { value: string } }) => {
@@ -102,7 +125,13 @@ const SingleField = (props: { field: Force.Field, validator: Validator }): JSX.E
 			});
 		}
 	};
-	if (field.datatype.t.kind == 'text') {
+	if (field.datatype.t.kind == 'image') {
+		return <>
+			{content[field.name] ? <img className={style.imagePreview} src={`/image/${content[field.name]}`} /> : null}
+			<ImageUpload onUploaded={id => input_props.onChange({ target: { value: id } })} />
+			{validate_info && <InvalidMessage msg={validate_info} />}
+		</>;
+	} else if (field.datatype.t.kind == 'text') {
 		return <>
 			<textarea {...input_props} />
 			{validate_info && <InvalidMessage msg={validate_info} />}
@@ -124,6 +153,8 @@ function ShowItem(props: { t: Force.BasicDataType, value: any }): JSX.Element {
 		</div>;
 	} else if (props.t.kind == 'bond') {
 		return <SimpleArticleCardById article_id={Number(props.value)} />;
+	} else if (props.t.kind == 'image') {
+		return <img className={style.imagePreview} src={`/image/${props.value}`} />;
 	} else {
 		return <>{props.value}</>;
 	}
@@ -166,6 +197,17 @@ const ArrayField = (props: { field: Force.Field, validator: Validator }): JSX.El
 		}
 	};
 
+	const push_image = (id: string): void => {
+		const next_state = produce(editor_panel_data, nxt => {
+			if (editor_panel_data.content[field.name] instanceof Array) {
+				(nxt.content[field.name] as string[]).push(id);
+			} else {
+				nxt.content[field.name] = [id];
+			}
+		});
+		setEditorPanelData(next_state);
+	};
+
 	const push_data = (): void => {
 		if (input_validate_info == undefined) {
 			const next_state = produce(editor_panel_data, nxt => {
@@ -191,7 +233,7 @@ const ArrayField = (props: { field: Force.Field, validator: Validator }): JSX.El
 	};
 
 	const input_props = {
-		placeholder: field.name,
+		placeholder: field.placeholder ?? field.name,
 		id: field.name,
 		value,
 		onChange: (evt: { target: { value: string } }) => {
@@ -200,7 +242,13 @@ const ArrayField = (props: { field: Force.Field, validator: Validator }): JSX.El
 				.then(res => setInputValidateInfo(res));
 		},
 	};
-	if (field.datatype.t.kind == 'text') {
+	if (field.datatype.t.kind == 'image') {
+		return <>
+			{array_validate_info && <InvalidMessage msg={array_validate_info} />}
+			{show_list()}
+			<ImageUpload onUploaded={push_image} />
+		</>;
+	} else if (field.datatype.t.kind == 'text') {
 		return <>
 			{array_validate_info && <InvalidMessage msg={array_validate_info} />}
 			{show_list()}
@@ -227,14 +275,17 @@ const Field = (props: { field: Force.Field, validator: Validator }): JSX.Element
 
 	const Wrap = (element: JSX.Element): JSX.Element => {
 		return <div key={field.name} className={style.field}>
-			<label htmlFor={field.name}>
+			<label htmlFor={field.name} title={field.description}>
 				{`${field.name}`}
 				<span className={style.dataType}>{`${Force.show_data_type(field.datatype)}`}</span>
 			</label>
 			{element}
 		</div>;
 	};
-	if (field.datatype.kind == 'single') {
+	if (field.datatype.t.kind == 'poll') {
+		// 投票欄位無須填寫，發文後即可投票
+		return Wrap(<div>{field.datatype.t.poll.options.join('、')}</div>);
+	} else if (field.datatype.kind == 'single') {
 		return Wrap(<SingleField {...props} />);
 	} else if (field.datatype.kind == 'optional') {
 		// TODO: 改爲可選
@@ -273,7 +324,9 @@ function _EditorBody(props: RouteComponentProps): JSX.Element {
 		// eslint-disable-next-line
 		let content: { [index: string]: any } = {};
 		for (let field of category.fields) {
-			if (field.datatype.t.kind == 'number' || field.datatype.t.kind == 'bond') {
+			if (field.datatype.t.kind == 'poll') {
+				content[field.name] = null;
+			} else if (field.datatype.t.kind == 'number' || field.datatype.t.kind == 'bond' || field.datatype.t.kind == 'image') {
 				if (field.datatype.kind == 'array') {
 					content[field.name] = (editor_panel_data.content[field.name] as string[]).map(Number);
 				} else {
@@ -295,17 +348,20 @@ function _EditorBody(props: RouteComponentProps): JSX.Element {
 
 				for (let field of category.fields) {
 					if (field.datatype.t.kind == 'bond') {
+						// TODO: 讓使用者選擇標籤，目前必填時先帶入第一個標籤
+						const tags = field.datatype.t.tags;
+						const tag = tags?.required ? tags.tags[0] : null;
 						if (field.datatype.kind == 'array') {
 							content[field.name] = content[field.name].map((id: number) => ({
 								energy: 0,
 								target_article: id,
-								tag: null
+								tag
 							}));
 						} else {
 							content[field.name] = {
 								energy: 0,
 								target_article: content[field.name],
-								tag: null
+								tag
 							};
 						}
 					}
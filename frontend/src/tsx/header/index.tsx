@@ -145,7 +145,7 @@ function _Header(props: RouteComponentProps): JSX.Element {
 						forced_expanded={expanding_user}
 						button={
 							<div className={style.userInfo} onClick={() => setExpandingUser(!expanding_user)}>
-								<img src={`/avatar/${user_state.user_name}`} />
+								<img src={`/avatar/${user_state.user_name}?size=48`} />
 								<div className={style.userName}>{user_state.user_name}</div>
 								<div className={style.energy}>☘ {user_state.energy}</div>
 							</div>}
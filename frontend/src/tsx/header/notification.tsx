@@ -154,5 +154,9 @@ export function NotificationBlock(props: { notification: Notification }): JSX.El
 			return <ReplyNoti txt="挺" />;
 		case NotificationKind.ArticleReplied:
 			return <ReplyNoti txt="回" />;
+		case NotificationKind.ReportResolved:
+			return <NotiConcreteRow>你對 <Link to={`/app/b/${n.board_name!}`}>{n.board_name!}</Link> 的文章 <Link to={`/app/b/${n.board_name!}/a/${n.article1_id!}`}>{n.article1_title}</Link> 的檢舉已處理</NotiConcreteRow>;
+		case NotificationKind.ElectionResult:
+			return <NotiConcreteRow><Link to={`/app/b/${n.board_name!}`}>{n.board_name!}</Link> 的選舉已開票</NotiConcreteRow>;
 	}
 }
\ No newline at end of file
@@ -33,7 +33,7 @@ function _Header(props: RouteComponentProps): JSX.Element {
 			return null;
 		}
 		return <div className={style.userInfo}>
-			<img src={`/avatar/${user_state.user_name}`} />
+			<img src={`/avatar/${user_state.user_name}?size=48`} />
 			<div className={style.userName}>{user_state.user_name}</div>
 			<div className={style.energy}>☘ {user_state.energy}</div>
 		</div>;
@@ -5,6 +5,7 @@ import { toast } from 'react-toastify';
 import style from '../../../css/mobile/menu.module.css';
 import { API_FETCHER, unwrap } from '../../../ts/api/api';
 import type { BoardOverview, Result, Error } from '../../../ts/api/api_trait';
+import { HotWindow } from '../../../ts/api/api_trait';
 import { BoardBlock } from '../../browsebar';
 import { LoginModal } from '../../header';
 import { UserState } from '../../global_state/user';
@@ -91,7 +92,7 @@ export function Menu(props: { onCoverClicked: () => void, userBlock: JSX.Element
         		{
         			user_state.login ? null : <Row onClick={() => setLogining(true)}>登入</Row>
         		}
-        		<BoardsRow name="熱門看板" fetchBoards={async () => await API_FETCHER.queryHotBoards()} />
+        		<BoardsRow name="熱門看板" fetchBoards={async () => await API_FETCHER.queryHotBoards(HotWindow.Now)} />
         		{
         			!user_state.login ? null : <>
                         <Row to={`/app/user_board/${user_state.user_name}`}>我的個板</Row>
@@ -2,7 +2,7 @@ import * as React from 'react';
 import { RouteComponentProps } from 'react-router';
 import { Redirect, Link } from 'react-router-dom';
 import { API_FETCHER, unwrap } from '../../ts/api/api';
-import { Party, BoardType } from '../../ts/api/api_trait';
+import { Party, BoardType, PartyMember, PartyPosition, Result, Error } from '../../ts/api/api_trait';
 import { BoardCreator } from '../board_switch/board_creator';
 import { EXILED_PARTY_NAME } from './index';
 import { UserState } from '../global_state/user';
@@ -52,13 +52,17 @@ export function PartyDetail(props: Props): JSX.Element {
 			</div>
 			{
 				(() => {
-					if (!party.board_id && user_state.login) {
-						return <CreateBoardBlock party_id={party.id} rp={props} />;
-					} else {
+					// 僅黨主席能代表政黨創板或遷移
+					if (!user_state.login || party.chairman_id != user_state.id) {
 						return null;
 					}
+					return <>
+						{party.board_id ? null : <CreateBoardBlock party_id={party.id} rp={props} />}
+						{party.board_id ? null : <RelocateBlock party={party} />}
+					</>;
 				})()
 			}
+			<PartyMembers party_id={party.id} />
 		</div>;
 	} else {
 		return <Redirect to="/app/party" />;
@@ -72,4 +76,135 @@ function CreateBoardBlock(props: { party_id: number, rp: Props }): JSX.Element {
 		<div onClick={() => setExpand(!expand)} className={style.createButton}>🏂 創立看板</div>
 		<BoardCreator board_type={BoardType.General} party_id={props.party_id} visible={expand} setVisible={setExpand} history={props.rp.history} />
 	</div>;
-}
\ No newline at end of file
+}
+
+function RelocateBlock(props: { party: Party }): JSX.Element {
+	let [board_name, setBoardName] = React.useState('');
+
+	async function relocate(): Promise<void> {
+		try {
+			unwrap(await API_FETCHER.relocateParty(props.party.id, board_name));
+			window.location.reload();
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+
+	return <div className={style.createBoardBlock}>
+		<input value={board_name} placeholder="看板名稱" onChange={evt => setBoardName(evt.target.value)} />
+		<button onClick={relocate} disabled={board_name.length == 0}>🚚 遷入看板</button>
+	</div>;
+}
+
+const POSITION_NAME: { [position in PartyPosition]: string } = {
+	[PartyPosition.Applicant]: '申請中',
+	[PartyPosition.Member]: '黨員',
+	[PartyPosition.Executive]: '幹部',
+	[PartyPosition.Chairman]: '黨主席',
+};
+
+const POSITION_LEVEL: { [position in PartyPosition]: number } = {
+	[PartyPosition.Applicant]: 0,
+	[PartyPosition.Member]: 1,
+	[PartyPosition.Executive]: 2,
+	[PartyPosition.Chairman]: 3,
+};
+
+function PartyMembers(props: { party_id: number }): JSX.Element {
+	const { user_state } = UserState.useContainer();
+	let [members, setMembers] = React.useState<PartyMember[]>([]);
+	const party_id = props.party_id;
+
+	const refresh = React.useCallback(() => {
+		API_FETCHER.queryPartyMembers(party_id)
+			.then(data => setMembers(unwrap(data)))
+			.catch(err => toastErr(err));
+	}, [party_id]);
+	React.useEffect(refresh, [refresh]);
+
+	const me = user_state.login ? members.find(m => m.user_id == user_state.id) : undefined;
+	const my_level = me ? POSITION_LEVEL[me.position] : -1;
+
+	async function run(action: () => Promise<Result<null, Error>>): Promise<void> {
+		try {
+			unwrap(await action());
+			refresh();
+		} catch (err) {
+			toastErr(err);
+		}
+	}
+
+	function Buttons(props: { member: PartyMember }): JSX.Element {
+		const { member } = props;
+		const level = POSITION_LEVEL[member.position];
+		if (me == undefined || member.user_id == me.user_id) {
+			return <></>;
+		}
+		if (member.position == PartyPosition.Applicant) {
+			if (my_level < POSITION_LEVEL[PartyPosition.Executive]) {
+				return <></>;
+			}
+			return <span>
+				<button onClick={() => run(() => API_FETCHER.reviewPartyApplication(party_id, member.user_id, true))}>接受</button>
+				<button onClick={() => run(() => API_FETCHER.reviewPartyApplication(party_id, member.user_id, false))}>拒絕</button>
+			</span>;
+		}
+		return <span>
+			{
+				my_level >= POSITION_LEVEL[PartyPosition.Executive] && level < my_level ?
+					<button onClick={() => run(() => API_FETCHER.expelPartyMember(party_id, member.user_id))}>開除</button> : null
+			}
+			{
+				me.position == PartyPosition.Chairman ?
+					<select value={member.position} onChange={evt => {
+						const position = evt.target.value as PartyPosition;
+						if (position == PartyPosition.Chairman && !window.confirm(`確定將黨主席交給 ${member.user_name}？`)) {
+							return;
+						}
+						run(() => API_FETCHER.setPartyPosition(party_id, member.user_id, position));
+					}}>
+						{
+							[PartyPosition.Member, PartyPosition.Executive, PartyPosition.Chairman].map(p =>
+								<option key={p} value={p}>{POSITION_NAME[p]}</option>)
+						}
+					</select> : null
+			}
+		</span>;
+	}
+
+	return <div className={style.members}>
+		<div>
+			{
+				user_state.login && me == undefined ?
+					<button onClick={() => run(() => API_FETCHER.applyParty(party_id))}>申請入黨</button> : null
+			}
+			{
+				me && me.position != PartyPosition.Chairman ?
+					<button onClick={() => run(() => API_FETCHER.leaveParty(party_id))}>
+						{me.position == PartyPosition.Applicant ? '撤回申請' : '退黨'}
+					</button> : null
+			}
+		</div>
+		{
+			me && me.position != PartyPosition.Applicant ?
+				<div>
+					奉獻比率
+					<input type="number" min={10} max={100} step={10} defaultValue={me.dedication_ratio}
+						onBlur={evt => {
+							const ratio = parseInt(evt.target.value);
+							if (ratio != me.dedication_ratio) {
+								run(() => API_FETCHER.setDedicationRatio(party_id, ratio));
+							}
+						}} />
+					%
+				</div> : null
+		}
+		{
+			members.map(member => <div key={member.user_id} className={style.member}>
+				<Link to={`/app/user/${member.user_name}`}>{member.user_name}</Link>
+				<span className={style.position}>{POSITION_NAME[member.position]}</span>
+				<Buttons member={member} />
+			</div>)
+		}
+	</div>;
+}
@@ -0,0 +1,12 @@
+-- 投票欄位的選票，複選時一人可有多筆
+CREATE TABLE poll_votes (
+  id bigserial PRIMARY KEY,
+  article_id bigint REFERENCES articles (id) NOT NULL,
+  field_name text NOT NULL,
+  user_id bigint REFERENCES users (id) NOT NULL,
+  choice text NOT NULL,
+  create_time timestamptz NOT NULL DEFAULT NOW(),
+  UNIQUE (article_id, field_name, user_id, choice)
+);
+
+CREATE INDEX poll_votes_article_id_index ON poll_votes (article_id);
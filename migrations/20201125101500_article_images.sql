@@ -0,0 +1,7 @@
+-- 文章中的圖片，大頭貼的 owner_id 爲 NULL
+ALTER TABLE images
+  ADD COLUMN owner_id bigint REFERENCES users (id),
+  ADD COLUMN content_type text NOT NULL DEFAULT 'image/png',
+  ADD COLUMN create_time timestamptz NOT NULL DEFAULT NOW();
+
+CREATE INDEX images_owner_id_index ON images (owner_id);
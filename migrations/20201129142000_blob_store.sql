@@ -0,0 +1,13 @@
+-- 二進位資料以內容雜湊爲鍵，實際內容存放於 blobs 表或檔案系統
+CREATE TABLE blobs (
+  key text PRIMARY KEY,
+  data bytea NOT NULL
+);
+
+ALTER TABLE images ADD COLUMN blob_key text;
+UPDATE images SET blob_key = encode(sha256(raw_data), 'hex');
+INSERT INTO blobs (key, data)
+  SELECT DISTINCT ON (blob_key) blob_key, raw_data FROM images
+  ON CONFLICT (key) DO NOTHING;
+ALTER TABLE images ALTER COLUMN blob_key SET NOT NULL;
+ALTER TABLE images DROP COLUMN raw_data;
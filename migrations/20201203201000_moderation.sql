@@ -0,0 +1,35 @@
+-- 看板管理：隱藏文章、鎖文（不可再被鍵結）、禁言
+ALTER TABLE articles
+  ADD COLUMN hidden boolean NOT NULL DEFAULT FALSE,
+  ADD COLUMN locked boolean NOT NULL DEFAULT FALSE;
+
+CREATE TABLE board_bans (
+  id bigserial PRIMARY KEY,
+  board_id bigint REFERENCES boards (id) NOT NULL,
+  user_id bigint REFERENCES users (id) NOT NULL,
+  until timestamptz, -- NULL 表永久
+  create_time timestamptz NOT NULL DEFAULT NOW(),
+  UNIQUE (board_id, user_id)
+);
+
+CREATE TYPE moderation_action AS ENUM (
+  'hide_article',
+  'unhide_article',
+  'lock_article',
+  'unlock_article',
+  'ban_user',
+  'unban_user'
+);
+
+CREATE TABLE moderation_logs (
+  id bigserial PRIMARY KEY,
+  board_id bigint REFERENCES boards (id) NOT NULL,
+  moderator_id bigint REFERENCES users (id) NOT NULL,
+  action moderation_action NOT NULL,
+  article_id bigint REFERENCES articles (id),
+  user_id bigint REFERENCES users (id),
+  reason text NOT NULL DEFAULT '',
+  create_time timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX moderation_logs_board_id_index ON moderation_logs (board_id);
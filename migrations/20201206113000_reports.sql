@@ -0,0 +1,30 @@
+-- 檢舉
+CREATE TYPE report_reason AS ENUM (
+  'spam',
+  'abuse',
+  'off_topic',
+  'other'
+);
+
+CREATE TYPE report_resolution AS ENUM (
+  'dismissed',
+  'hidden',
+  'banned'
+);
+
+CREATE TABLE reports (
+  id bigserial PRIMARY KEY,
+  article_id bigint REFERENCES articles (id) NOT NULL,
+  reporter_id bigint REFERENCES users (id) NOT NULL,
+  reason report_reason NOT NULL,
+  detail text NOT NULL DEFAULT '',
+  resolution report_resolution, -- NULL 表尚未處理
+  resolver_id bigint REFERENCES users (id),
+  create_time timestamptz NOT NULL DEFAULT NOW(),
+  resolve_time timestamptz,
+  UNIQUE (article_id, reporter_id)
+);
+
+CREATE INDEX reports_article_id_index ON reports (article_id);
+
+ALTER TYPE notification_kind ADD VALUE 'report_resolved';
@@ -0,0 +1,12 @@
+-- 看板資訊的修改紀錄，每筆存修改後的完整內容
+CREATE TABLE board_histories (
+  id bigserial PRIMARY KEY,
+  board_id bigint REFERENCES boards (id) NOT NULL,
+  editor_id bigint REFERENCES users (id) NOT NULL,
+  title text NOT NULL,
+  detail text NOT NULL,
+  force text NOT NULL,
+  create_time timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX board_histories_board_id_index ON board_histories (board_id);
@@ -0,0 +1,13 @@
+-- 置頂與精華文章
+ALTER TABLE articles
+  ADD COLUMN pinned boolean NOT NULL DEFAULT FALSE,
+  ADD COLUMN pin_time timestamptz,
+  ADD COLUMN featured boolean NOT NULL DEFAULT FALSE;
+
+CREATE INDEX articles_pinned_index ON articles (board_id) WHERE pinned;
+CREATE INDEX articles_featured_index ON articles (board_id) WHERE featured;
+
+ALTER TYPE moderation_action ADD VALUE 'pin_article';
+ALTER TYPE moderation_action ADD VALUE 'unpin_article';
+ALTER TYPE moderation_action ADD VALUE 'feature_article';
+ALTER TYPE moderation_action ADD VALUE 'unfeature_article';
@@ -0,0 +1,46 @@
+-- 政黨成員的地位： 0 申請中、1 黨員、2 幹部、3 黨主席
+ALTER TABLE party_members
+  ADD COLUMN position smallint NOT NULL DEFAULT 1 CHECK (position BETWEEN 0 AND 3);
+
+ALTER TABLE parties
+  ADD COLUMN chairman_id bigint REFERENCES users (id);
+
+-- 既有政黨以最早入黨者爲黨主席
+UPDATE
+  parties
+SET
+  chairman_id = (
+    SELECT
+      user_id
+    FROM
+      party_members
+    WHERE
+      party_id = parties.id
+    ORDER BY
+      create_time,
+      id
+    LIMIT 1);
+
+UPDATE
+  party_members
+SET
+  position = 3
+FROM
+  parties
+WHERE
+  parties.id = party_members.party_id
+  AND parties.chairman_id = party_members.user_id;
+
+ALTER TABLE parties
+  ALTER COLUMN chairman_id SET NOT NULL;
+
+-- 同一人在同一政黨只有一筆紀錄
+DELETE FROM party_members a USING party_members b
+WHERE a.party_id = b.party_id
+  AND a.user_id = b.user_id
+  AND a.id > b.id;
+
+ALTER TABLE party_members
+  ADD CONSTRAINT party_members_party_user_unique UNIQUE (party_id, user_id);
+
+CREATE INDEX party_members_user_id_index ON party_members (user_id);
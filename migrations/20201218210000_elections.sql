@@ -0,0 +1,41 @@
+-- 看板定期改選，開票後由得票最多的政黨執政
+CREATE TABLE elections (
+  id bigserial PRIMARY KEY,
+  board_id bigint REFERENCES boards (id) NOT NULL,
+  start_time timestamptz NOT NULL DEFAULT NOW(),
+  end_time timestamptz NOT NULL,
+  tallied boolean NOT NULL DEFAULT FALSE,
+  winner_party_id bigint REFERENCES parties (id),
+  CHECK (end_time > start_time)
+);
+
+-- 每個看板同時只有一場尚未開票的選舉
+CREATE UNIQUE INDEX elections_board_id_open_index ON elections (board_id)
+WHERE
+  NOT tallied;
+
+CREATE INDEX elections_end_time_index ON elections (end_time)
+WHERE
+  NOT tallied;
+
+CREATE TABLE election_candidates (
+  id bigserial PRIMARY KEY,
+  election_id bigint REFERENCES elections (id) NOT NULL,
+  party_id bigint REFERENCES parties (id) NOT NULL,
+  create_time timestamptz NOT NULL DEFAULT NOW(),
+  UNIQUE (election_id, party_id)
+);
+
+-- 一人一票，投票期間內可改投
+CREATE TABLE election_votes (
+  id bigserial PRIMARY KEY,
+  election_id bigint REFERENCES elections (id) NOT NULL,
+  voter_id bigint REFERENCES users (id) NOT NULL,
+  party_id bigint REFERENCES parties (id) NOT NULL,
+  create_time timestamptz NOT NULL DEFAULT NOW(),
+  UNIQUE (election_id, voter_id),
+  FOREIGN KEY (election_id, party_id) REFERENCES election_candidates (election_id, party_id)
+);
+
+ALTER TYPE notification_kind
+  ADD VALUE 'election_result';
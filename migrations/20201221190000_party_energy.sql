@@ -0,0 +1,17 @@
+-- 黨員奉獻鍵能的比率，10~100 的整數
+ALTER TABLE party_members
+  ADD COLUMN dedication_ratio smallint NOT NULL DEFAULT 10 CHECK (dedication_ratio BETWEEN 10 AND 100);
+
+-- 黨員文章在該黨看板上獲得的鍵能，依奉獻比率轉入政黨的紀錄
+CREATE TABLE party_energy_transfers (
+  id bigserial PRIMARY KEY,
+  party_id bigint REFERENCES parties (id) NOT NULL,
+  user_id bigint REFERENCES users (id) NOT NULL,
+  article_id bigint REFERENCES articles (id) NOT NULL,
+  energy int NOT NULL,
+  create_time timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX party_energy_transfers_party_id_index ON party_energy_transfers (party_id);
+
+CREATE INDEX parties_board_id_energy_index ON parties (board_id, energy DESC);
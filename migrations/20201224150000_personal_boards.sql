@@ -0,0 +1,8 @@
+-- 個人看板的擁有者，一般看板爲 NULL
+ALTER TABLE boards
+  ADD COLUMN owner_id bigint REFERENCES users (id);
+
+ALTER TABLE boards
+  ADD CONSTRAINT boards_personal_owner_check CHECK ((board_type = 'personal') = (owner_id IS NOT NULL));
+
+CREATE UNIQUE INDEX boards_owner_id_index ON boards (owner_id);
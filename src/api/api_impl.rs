@@ -100,6 +100,32 @@ impl api_trait::ArticleQueryRouter for ArticleQueryRouter {
         };
         complete_article(articles, context).await
     }
+    async fn query_featured_article_list(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+        count: usize,
+        max_id: Option<i64>,
+    ) -> Fallible<Vec<model::ArticleMeta>> {
+        let articles: Vec<_> = db::article::get_featured(board_id, max_id, count)
+            .await?
+            .collect();
+        complete_article(articles, context).await
+    }
+    async fn query_hot_articles(
+        &self,
+        context: &mut crate::Ctx,
+        board_name: Option<String>,
+        window: super::model::HotWindow,
+    ) -> Fallible<Vec<model::ArticleMeta>> {
+        let board_id = match board_name {
+            Some(name) => Some(db::board::get_by_name(&name, "general").await?.id),
+            None => None,
+        };
+        let article_ids = service::hot_articles::get_hot_articles(window, board_id).await?;
+        let articles = db::article::get_meta_by_ids(&article_ids).await?;
+        complete_article(articles, context).await
+    }
     async fn query_article(&self, context: &mut crate::Ctx, id: i64) -> Fallible<model::Article> {
         let article = db::article::get_by_id(id).await?;
         complete_article(article, context).await
@@ -109,11 +135,17 @@ impl api_trait::ArticleQueryRouter for ArticleQueryRouter {
         context: &mut crate::Ctx,
         id: i64,
         category_set: Option<Vec<String>>,
+        tag_set: Option<Vec<String>>,
         family_filter: super::model::FamilyFilter,
     ) -> Result<Vec<(super::model::Edge, super::model::Article)>, crate::custom_error::Error> {
-        let bonders: Vec<_> = db::article::get_bonder(id, opt_slice(&category_set), &family_filter)
-            .await?
-            .collect();
+        let bonders: Vec<_> = db::article::get_bonder(
+            id,
+            opt_slice(&category_set),
+            opt_slice(&tag_set),
+            &family_filter,
+        )
+        .await?
+        .collect();
         complete_article(bonders, context).await
     }
     async fn query_bonder_meta(
@@ -121,13 +153,18 @@ impl api_trait::ArticleQueryRouter for ArticleQueryRouter {
         context: &mut crate::Ctx,
         id: i64,
         category_set: Option<Vec<String>>,
+        tag_set: Option<Vec<String>>,
         family_filter: super::model::FamilyFilter,
     ) -> Result<Vec<(super::model::Edge, super::model::ArticleMeta)>, crate::custom_error::Error>
     {
-        let bonders: Vec<_> =
-            db::article::get_bonder_meta(id, opt_slice(&category_set), &family_filter)
-                .await?
-                .collect();
+        let bonders: Vec<_> = db::article::get_bonder_meta(
+            id,
+            opt_slice(&category_set),
+            opt_slice(&tag_set),
+            &family_filter,
+        )
+        .await?
+        .collect();
         complete_article(bonders, context).await
     }
     async fn query_article_meta(
@@ -164,17 +201,39 @@ impl api_trait::ArticleQueryRouter for ArticleQueryRouter {
         context: &mut crate::Ctx,
         article_id: i64,
         category_set: Option<Vec<String>>,
+        tag_set: Option<Vec<String>>,
         family_filter: super::model::FamilyFilter,
     ) -> Result<super::model::Graph, crate::custom_error::Error> {
         let graph = service::graph_view::query_graph(
             10,
             article_id,
             opt_slice(&category_set),
+            opt_slice(&tag_set),
             &family_filter,
         )
         .await?;
         complete_article(graph, context).await
     }
+    async fn cast_vote(
+        &self,
+        context: &mut crate::Ctx,
+        article_id: i64,
+        field_name: String,
+        choices: Vec<String>,
+    ) -> Fallible<Vec<model::PollTally>> {
+        let id = context.get_id_strict().await?;
+        db::poll::cast_vote(id, article_id, &field_name, choices).await
+    }
+    async fn report_article(
+        &self,
+        context: &mut crate::Ctx,
+        article_id: i64,
+        reason: model::ReportReason,
+        detail: Option<String>,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::report::create(id, article_id, reason, &detail.unwrap_or_default()).await
+    }
 }
 
 #[derive(Default)]
@@ -206,6 +265,75 @@ impl api_trait::PartyQueryRouter for PartyQueryRouter {
     ) -> Fallible<Vec<model::Party>> {
         db::party::get_by_board_id(board_id).await
     }
+    async fn query_party_members(
+        &self,
+        _context: &mut crate::Ctx,
+        party_id: i64,
+    ) -> Fallible<Vec<model::PartyMember>> {
+        db::party::get_members(party_id).await
+    }
+    async fn apply_party(&self, context: &mut crate::Ctx, party_id: i64) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::apply(party_id, id).await
+    }
+    async fn review_party_application(
+        &self,
+        context: &mut crate::Ctx,
+        party_id: i64,
+        user_id: i64,
+        accept: bool,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::review_application(id, party_id, user_id, accept).await
+    }
+    async fn leave_party(&self, context: &mut crate::Ctx, party_id: i64) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::leave(party_id, id).await
+    }
+    async fn expel_party_member(
+        &self,
+        context: &mut crate::Ctx,
+        party_id: i64,
+        user_id: i64,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::expel(id, party_id, user_id).await
+    }
+    async fn set_dedication_ratio(
+        &self,
+        context: &mut crate::Ctx,
+        party_id: i64,
+        ratio: i16,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::set_dedication_ratio(party_id, id, ratio).await
+    }
+    async fn query_party_energy_ranking(
+        &self,
+        _context: &mut crate::Ctx,
+        board_id: i64,
+    ) -> Fallible<Vec<model::Party>> {
+        db::party::get_energy_ranking(board_id).await
+    }
+    async fn relocate_party(
+        &self,
+        context: &mut crate::Ctx,
+        party_id: i64,
+        board_name: String,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::relocate(id, party_id, &board_name).await
+    }
+    async fn set_party_position(
+        &self,
+        context: &mut crate::Ctx,
+        party_id: i64,
+        user_id: i64,
+        position: model::PartyPosition,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::party::set_position(id, party_id, user_id, position).await
+    }
 }
 
 #[derive(Default)]
@@ -237,6 +365,16 @@ impl api_trait::BoardQueryRouter for BoardQueryRouter {
         }
         board.assign_props().await
     }
+    async fn query_personal_board(
+        &self,
+        _context: &mut crate::Ctx,
+        user_name: String,
+    ) -> Fallible<model::Board> {
+        db::board::get_personal(&user_name)
+            .await?
+            .assign_props()
+            .await
+    }
     async fn query_board_by_id(&self, context: &mut crate::Ctx, id: i64) -> Fallible<model::Board> {
         let board = db::board::get_by_id(id).await?;
         if let Some(user_id) = context.get_id().await {
@@ -246,10 +384,11 @@ impl api_trait::BoardQueryRouter for BoardQueryRouter {
     }
     async fn create_board(
         &self,
-        _context: &mut crate::Ctx,
+        context: &mut crate::Ctx,
         new_board: model::NewBoard,
     ) -> Fallible<i64> {
-        Ok(db::board::create(&new_board).await?)
+        let id = context.get_id_strict().await?;
+        db::board::found(id, &new_board).await
     }
     async fn query_subscribed_user_count(
         &self,
@@ -261,12 +400,158 @@ impl api_trait::BoardQueryRouter for BoardQueryRouter {
     async fn query_hot_boards(
         &self,
         _context: &mut crate::Ctx,
+        window: model::HotWindow,
     ) -> Result<Vec<super::model::BoardOverview>, crate::custom_error::Error> {
-        let board_ids = service::hot_boards::get_hot_boards().await?;
-        db::board::get_overview(&board_ids)
-            .await?
-            .assign_props()
-            .await
+        let board_ids = service::hot_boards::get_hot_boards(window).await?;
+        let mut boards = db::board::get_overview(&board_ids).await?;
+        // 資料庫不保證順序，依熱門程度重排
+        boards.sort_by_key(|b| board_ids.iter().position(|id| *id == b.id));
+        boards.assign_props().await
+    }
+    async fn board_heartbeat(&self, context: &mut crate::Ctx, board_id: i64) -> Fallible<()> {
+        if let Some(user_id) = context.get_id().await {
+            service::hot_boards::set_board_pop(user_id, board_id).await?;
+        }
+        Ok(())
+    }
+    async fn update_board(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+        title: Option<String>,
+        detail: Option<String>,
+        force: Option<String>,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::board::update(id, board_id, title, detail, force).await
+    }
+    async fn query_board_history(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+    ) -> Fallible<Vec<model::BoardHistory>> {
+        let id = context.get_id_strict().await?;
+        db::moderation::check_board_member(board_id, id).await?;
+        db::board::get_history(board_id).await
+    }
+    async fn query_is_moderator(&self, context: &mut crate::Ctx, board_id: i64) -> Fallible<bool> {
+        match context.get_id().await {
+            Some(user_id) => db::moderation::is_moderator(board_id, user_id).await,
+            None => Ok(false),
+        }
+    }
+    async fn hide_article(
+        &self,
+        context: &mut crate::Ctx,
+        article_id: i64,
+        hidden: bool,
+        reason: String,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::moderation::set_hidden(id, article_id, hidden, &reason).await
+    }
+    async fn lock_article(
+        &self,
+        context: &mut crate::Ctx,
+        article_id: i64,
+        locked: bool,
+        reason: String,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::moderation::set_locked(id, article_id, locked, &reason).await
+    }
+    async fn ban_user(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+        user_id: i64,
+        days: Option<i64>,
+        reason: String,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::moderation::ban(id, board_id, user_id, days, &reason).await
+    }
+    async fn unban_user(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+        user_id: i64,
+        reason: String,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::moderation::unban(id, board_id, user_id, &reason).await
+    }
+    async fn pin_article(
+        &self,
+        context: &mut crate::Ctx,
+        article_id: i64,
+        pinned: bool,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::moderation::set_pinned(id, article_id, pinned).await
+    }
+    async fn feature_article(
+        &self,
+        context: &mut crate::Ctx,
+        article_id: i64,
+        featured: bool,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::moderation::set_featured(id, article_id, featured).await
+    }
+    async fn query_moderation_log(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+    ) -> Fallible<Vec<model::ModerationLog>> {
+        let id = context.get_id_strict().await?;
+        db::moderation::check_board_member(board_id, id).await?;
+        db::moderation::get_log(board_id).await
+    }
+    async fn query_election(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+    ) -> Fallible<Option<model::Election>> {
+        let id = context.get_id().await;
+        db::election::get_latest(board_id, id).await
+    }
+    async fn register_candidate(
+        &self,
+        context: &mut crate::Ctx,
+        election_id: i64,
+        party_id: i64,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::election::register(id, election_id, party_id).await
+    }
+    async fn vote_election(
+        &self,
+        context: &mut crate::Ctx,
+        election_id: i64,
+        party_id: i64,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        db::election::vote(id, election_id, party_id).await
+    }
+    async fn query_report_queue(
+        &self,
+        context: &mut crate::Ctx,
+        board_id: i64,
+    ) -> Fallible<Vec<model::Report>> {
+        let id = context.get_id_strict().await?;
+        db::moderation::check_moderator(board_id, id).await?;
+        db::report::get_queue(board_id).await
+    }
+    async fn resolve_report(
+        &self,
+        context: &mut crate::Ctx,
+        report_id: i64,
+        resolution: model::ReportResolution,
+        ban_days: Option<i64>,
+    ) -> Fallible<()> {
+        let id = context.get_id_strict().await?;
+        service::report::resolve(id, report_id, resolution, ban_days).await
     }
     async fn query_category_by_id(
         &self,
@@ -276,6 +561,17 @@ impl api_trait::BoardQueryRouter for BoardQueryRouter {
         let source = db::board::get_category_by_id(id).await?;
         Ok(source)
     }
+    async fn query_category_schema(
+        &self,
+        _context: &mut crate::Ctx,
+        board_id: i64,
+        category_name: String,
+    ) -> Result<String, crate::custom_error::Error> {
+        let category = db::article::get_newest_category(board_id, &category_name).await?;
+        let category = force::parse_category(&category.source)?;
+        let schema = force::json_schema::category_to_json_schema(&category);
+        Ok(schema.to_string())
+    }
 }
 
 #[derive(Default)]
@@ -501,6 +797,10 @@ impl api_trait::UserQueryRouter for UserQueryRouter {
         let id = context.get_id_strict().await?;
         db::avatar::update_avatar(id, image).await
     }
+    async fn upload_image(&self, context: &mut crate::Ctx, image: String) -> Fallible<i64> {
+        let id = context.get_id_strict().await?;
+        db::image::upload(id, image).await
+    }
     async fn update_sentence(
         &self,
         context: &mut crate::Ctx,
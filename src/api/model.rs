@@ -38,6 +38,25 @@ mod model {
         pub board_name: Option<String>,
         pub energy: i32,
         pub ruling: bool,
+        pub chairman_id: i64,
+        pub create_time: DateTime<Utc>,
+    }
+    /// 由低到高排列，存入資料庫時爲 0~3 的整數
+    #[derive(
+        Serialize, Deserialize, TypeScriptify, Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd,
+    )]
+    pub enum PartyPosition {
+        Applicant,
+        Member,
+        Executive,
+        Chairman,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct PartyMember {
+        pub user_id: i64,
+        pub user_name: String,
+        pub position: PartyPosition,
+        pub dedication_ratio: i16,
         pub create_time: DateTime<Utc>,
     }
     #[derive(
@@ -59,8 +78,42 @@ mod model {
         pub detail: String,
         pub force: String,
         pub ruling_party_id: i64,
+        // 個人看板的擁有者
+        pub owner_id: Option<i64>,
         pub popularity: i64,
     }
+    /// 熱門看板的時間窗，越長的窗衰減越慢
+    #[derive(
+        Serialize,
+        Deserialize,
+        TypeScriptify,
+        Clone,
+        Copy,
+        EnumString,
+        strum::ToString,
+        Debug,
+        Eq,
+        PartialEq,
+        Hash,
+    )]
+    pub enum HotWindow {
+        #[strum(serialize = "now")]
+        Now,
+        #[strum(serialize = "today")]
+        Today,
+        #[strum(serialize = "week")]
+        Week,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct BoardHistory {
+        pub id: i64,
+        pub editor_id: i64,
+        pub editor_name: String,
+        pub title: String,
+        pub detail: String,
+        pub force: String,
+        pub create_time: DateTime<Utc>,
+    }
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
     pub struct BoardName {
         pub id: i64,
@@ -78,6 +131,18 @@ mod model {
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug, Default)]
     pub struct ArticlePersonalMeta {
         pub is_favorite: bool,
+        pub votes: Vec<PollVote>,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct PollVote {
+        pub field_name: String,
+        pub choices: Vec<String>,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct PollTally {
+        pub field_name: String,
+        pub choice: String,
+        pub count: i64,
     }
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
     pub struct ArticleDigest {
@@ -100,6 +165,10 @@ mod model {
         pub digest: ArticleDigest,
         pub category_families: Vec<String>,
         pub create_time: DateTime<chrono::Utc>,
+        // 被執政黨鎖定的文章不可再被鍵結
+        pub locked: bool,
+        pub pinned: bool,
+        pub featured: bool,
 
         pub stat: ArticleStatistics,
         pub personal_meta: ArticlePersonalMeta,
@@ -122,6 +191,8 @@ mod model {
     pub struct ArticleStatistics {
         pub replies: i64,
         pub satellite_replies: i64,
+        // 只列出有人投的選項
+        pub polls: Vec<PollTally>,
     }
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
     pub struct Article {
@@ -179,6 +250,10 @@ mod model {
         ArticleGoodReplied,
         #[strum(serialize = "article_bad_replied")]
         ArticleBadReplied,
+        #[strum(serialize = "report_resolved")]
+        ReportResolved,
+        #[strum(serialize = "election_result")]
+        ElectionResult,
     }
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
     pub struct Notification {
@@ -198,6 +273,128 @@ mod model {
         pub article1_id: Option<i64>,
         pub article2_id: Option<i64>,
     }
+    #[derive(
+        Serialize,
+        Deserialize,
+        TypeScriptify,
+        Clone,
+        Copy,
+        EnumString,
+        strum::ToString,
+        Debug,
+        Eq,
+        PartialEq,
+    )]
+    pub enum ModerationAction {
+        #[strum(serialize = "hide_article")]
+        HideArticle,
+        #[strum(serialize = "unhide_article")]
+        UnhideArticle,
+        #[strum(serialize = "lock_article")]
+        LockArticle,
+        #[strum(serialize = "unlock_article")]
+        UnlockArticle,
+        #[strum(serialize = "ban_user")]
+        BanUser,
+        #[strum(serialize = "unban_user")]
+        UnbanUser,
+        #[strum(serialize = "pin_article")]
+        PinArticle,
+        #[strum(serialize = "unpin_article")]
+        UnpinArticle,
+        #[strum(serialize = "feature_article")]
+        FeatureArticle,
+        #[strum(serialize = "unfeature_article")]
+        UnfeatureArticle,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct ModerationLog {
+        pub id: i64,
+        pub board_id: i64,
+        pub moderator_id: i64,
+        pub moderator_name: String,
+        pub action: ModerationAction,
+        pub article_id: Option<i64>,
+        pub article_title: Option<String>,
+        pub user_id: Option<i64>,
+        pub user_name: Option<String>,
+        pub reason: String,
+        pub create_time: DateTime<Utc>,
+    }
+    #[derive(
+        Serialize,
+        Deserialize,
+        TypeScriptify,
+        Clone,
+        Copy,
+        EnumString,
+        strum::ToString,
+        Debug,
+        Eq,
+        PartialEq,
+    )]
+    pub enum ReportReason {
+        #[strum(serialize = "spam")]
+        Spam,
+        #[strum(serialize = "abuse")]
+        Abuse,
+        #[strum(serialize = "off_topic")]
+        OffTopic,
+        #[strum(serialize = "other")]
+        Other,
+    }
+    #[derive(
+        Serialize,
+        Deserialize,
+        TypeScriptify,
+        Clone,
+        Copy,
+        EnumString,
+        strum::ToString,
+        Debug,
+        Eq,
+        PartialEq,
+    )]
+    pub enum ReportResolution {
+        #[strum(serialize = "dismissed")]
+        Dismissed,
+        #[strum(serialize = "hidden")]
+        Hidden,
+        #[strum(serialize = "banned")]
+        Banned,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct Report {
+        pub id: i64,
+        pub article_id: i64,
+        pub article_title: String,
+        pub author_id: i64,
+        pub author_name: String,
+        pub reporter_id: i64,
+        pub reporter_name: String,
+        pub reason: ReportReason,
+        pub detail: String,
+        pub create_time: DateTime<Utc>,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct ElectionCandidate {
+        pub party_id: i64,
+        pub party_name: String,
+        // 開票前不公布票數
+        pub votes: Option<i64>,
+    }
+    #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
+    pub struct Election {
+        pub id: i64,
+        pub board_id: i64,
+        pub start_time: DateTime<Utc>,
+        pub end_time: DateTime<Utc>,
+        pub tallied: bool,
+        pub winner_party_id: Option<i64>,
+        pub candidates: Vec<ElectionCandidate>,
+        // 目前使用者所投的政黨
+        pub my_vote: Option<i64>,
+    }
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug)]
     pub enum SearchField {
         String(String),
@@ -210,6 +407,7 @@ mod model {
         pub to: i64,
         pub energy: i16,
         pub name: String,
+        pub tag: Option<String>,
     }
     #[derive(Serialize, Deserialize, TypeScriptify, Clone, Debug, Default)]
     pub struct Graph {
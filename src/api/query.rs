@@ -75,6 +75,9 @@ pub enum UserQuery {
     DeactivateSignupInvitation { signup_invitation_id: i64 },
     #[chitin(request, response = "()")]
     UpdateAvatar { image: String },
+    // 以 base64 編碼，回傳圖片 id ，供文章的圖片欄位引用
+    #[chitin(request, response = "i64")]
+    UploadImage { image: String },
     #[chitin(request, response = "()")]
     UpdateSentence { sentence: String },
     #[chitin(request, response = "()")]
@@ -96,6 +99,32 @@ pub enum PartyQuery {
     },
     #[chitin(request, response = "Vec<super::model::Party>")]
     QueryBoardPartyList { board_id: i64 },
+    #[chitin(request, response = "Vec<super::model::PartyMember>")]
+    QueryPartyMembers { party_id: i64 },
+    #[chitin(request, response = "()")]
+    ApplyParty { party_id: i64 },
+    #[chitin(request, response = "()")]
+    ReviewPartyApplication {
+        party_id: i64,
+        user_id: i64,
+        accept: bool,
+    },
+    #[chitin(request, response = "()")]
+    LeaveParty { party_id: i64 },
+    #[chitin(request, response = "()")]
+    ExpelPartyMember { party_id: i64, user_id: i64 },
+    #[chitin(request, response = "()")]
+    SetDedicationRatio { party_id: i64, ratio: i16 },
+    #[chitin(request, response = "Vec<super::model::Party>")]
+    QueryPartyEnergyRanking { board_id: i64 },
+    #[chitin(request, response = "()")]
+    RelocateParty { party_id: i64, board_name: String },
+    #[chitin(request, response = "()")]
+    SetPartyPosition {
+        party_id: i64,
+        user_id: i64,
+        position: super::model::PartyPosition,
+    },
 }
 #[derive(Serialize, Deserialize, ChitinCodegen, Debug, Clone)]
 pub enum ArticleQuery {
@@ -107,6 +136,18 @@ pub enum ArticleQuery {
         board_name: Option<String>,
         family_filter: super::model::FamilyFilter,
     },
+    #[chitin(request, response = "Vec<super::model::ArticleMeta>")]
+    QueryFeaturedArticleList {
+        board_id: i64,
+        count: usize,
+        max_id: Option<i64>,
+    },
+    // 未給 board_name 則爲全站熱門文章
+    #[chitin(request, response = "Vec<super::model::ArticleMeta>")]
+    QueryHotArticles {
+        board_name: Option<String>,
+        window: super::model::HotWindow,
+    },
     #[chitin(request, response = "super::model::Article")]
     QueryArticle { id: i64 },
     #[chitin(request, response = "super::model::ArticleMeta")]
@@ -115,6 +156,7 @@ pub enum ArticleQuery {
     QueryBonder {
         id: i64,
         category_set: Option<Vec<String>>,
+        tag_set: Option<Vec<String>>,
         family_filter: super::model::FamilyFilter,
     },
     #[chitin(
@@ -124,6 +166,7 @@ pub enum ArticleQuery {
     QueryBonderMeta {
         id: i64,
         category_set: Option<Vec<String>>,
+        tag_set: Option<Vec<String>>,
         family_filter: super::model::FamilyFilter,
     },
     #[chitin(request, response = "i64")]
@@ -147,8 +190,21 @@ pub enum ArticleQuery {
     QueryGraph {
         article_id: i64,
         category_set: Option<Vec<String>>,
+        tag_set: Option<Vec<String>>,
         family_filter: super::model::FamilyFilter,
     },
+    #[chitin(request, response = "()")]
+    ReportArticle {
+        article_id: i64,
+        reason: super::model::ReportReason,
+        detail: Option<String>,
+    },
+    #[chitin(request, response = "Vec<super::model::PollTally>")]
+    CastVote {
+        article_id: i64,
+        field_name: String,
+        choices: Vec<String>,
+    },
 }
 #[derive(Serialize, Deserialize, ChitinCodegen, Debug, Clone)]
 pub enum BoardQuery {
@@ -157,18 +213,90 @@ pub enum BoardQuery {
     #[chitin(request, response = "Vec<super::model::BoardName>")]
     QueryBoardNameList {},
     #[chitin(request, response = "super::model::Board")]
+    // style 爲看板類型， general 或 personal
     QueryBoard { name: String, style: String },
     #[chitin(request, response = "super::model::Board")]
+    QueryPersonalBoard { user_name: String },
+    #[chitin(request, response = "super::model::Board")]
     QueryBoardById { id: i64 },
     #[chitin(request, response = "usize")]
     QuerySubscribedUserCount { id: i64 },
     #[chitin(request, response = "i64")]
     CreateBoard { new_board: super::model::NewBoard },
     #[chitin(request, response = "Vec<super::model::BoardOverview>")]
-    QueryHotBoards {},
+    QueryHotBoards { window: super::model::HotWindow },
+    // 前端在看板頁上定期送出，逾時未送即不再計入看板人氣
+    #[chitin(request, response = "()")]
+    BoardHeartbeat { board_id: i64 },
+    // 未給的欄位維持原樣，力語言中內容有變的分類會產生新版本
+    #[chitin(request, response = "()")]
+    UpdateBoard {
+        board_id: i64,
+        title: Option<String>,
+        detail: Option<String>,
+        force: Option<String>,
+    },
+    #[chitin(request, response = "Vec<super::model::BoardHistory>")]
+    QueryBoardHistory { board_id: i64 },
+
+    #[chitin(request, response = "bool")]
+    QueryIsModerator { board_id: i64 },
+    #[chitin(request, response = "()")]
+    HideArticle {
+        article_id: i64,
+        hidden: bool,
+        reason: String,
+    },
+    #[chitin(request, response = "()")]
+    LockArticle {
+        article_id: i64,
+        locked: bool,
+        reason: String,
+    },
+    // days 爲 None 時永久禁言
+    #[chitin(request, response = "()")]
+    BanUser {
+        board_id: i64,
+        user_id: i64,
+        days: Option<i64>,
+        reason: String,
+    },
+    #[chitin(request, response = "()")]
+    UnbanUser {
+        board_id: i64,
+        user_id: i64,
+        reason: String,
+    },
+    // 每個看板最多置頂 db::moderation::MAX_PINNED 篇
+    #[chitin(request, response = "()")]
+    PinArticle { article_id: i64, pinned: bool },
+    #[chitin(request, response = "()")]
+    FeatureArticle { article_id: i64, featured: bool },
+    #[chitin(request, response = "Vec<super::model::ModerationLog>")]
+    QueryModerationLog { board_id: i64 },
+    #[chitin(request, response = "Option<super::model::Election>")]
+    QueryElection { board_id: i64 },
+    #[chitin(request, response = "()")]
+    RegisterCandidate { election_id: i64, party_id: i64 },
+    #[chitin(request, response = "()")]
+    VoteElection { election_id: i64, party_id: i64 },
+    #[chitin(request, response = "Vec<super::model::Report>")]
+    QueryReportQueue { board_id: i64 },
+    // 同一篇文章未處理的檢舉會一併結案，ban_days 僅在禁言時使用
+    #[chitin(request, response = "()")]
+    ResolveReport {
+        report_id: i64,
+        resolution: super::model::ReportResolution,
+        ban_days: Option<i64>,
+    },
 
     #[chitin(request, response = "String")]
     QueryCategoryById { id: i64 },
+    #[chitin(request, response = "String")]
+    QueryCategorySchema {
+        board_id: i64,
+        category_name: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, ChitinCodegen, Debug, Clone)]
@@ -1,8 +1,8 @@
 use carbonbond::{
     api::model::User,
-    config::{get_config, init as init_config},
+    config::{get_config, init as init_config, BlobStoreKind, KvStoreKind},
     custom_error::{Error, ErrorCode, Fallible},
-    db,
+    db, redis, service,
 };
 use rustyline::Editor;
 use sqlx::migrate::{Migrate, MigrateError, Migrator};
@@ -38,6 +38,14 @@ enum Root {
     Reset(Reset),
     #[structopt(about = "資料庫遷移", alias = "m")]
     Migrate,
+    #[structopt(about = "搬移圖片的存放處（postgres 或 fs）")]
+    MigrateBlob(MigrateBlob),
+    #[structopt(about = "爲尚無個人看板的使用者補建個人看板")]
+    BackfillPersonalBoards,
+    #[structopt(about = "檢查所有看板的力能否以目前的語法解析")]
+    CheckForce,
+    #[structopt(about = "列出背景工作最近一次執行的狀態")]
+    Jobs,
     #[structopt(about = "列出資料庫", alias = "l")]
     List,
     #[structopt(about = "往資料庫塞點什麼", alias = "a")]
@@ -49,6 +57,13 @@ struct Reset {
     no_migrate: bool,
 }
 #[derive(StructOpt, Debug)]
+struct MigrateBlob {
+    from: BlobStoreKind,
+    to: BlobStoreKind,
+    #[structopt(short, long, help = "搬移後刪除原處的資料")]
+    delete: bool,
+}
+#[derive(StructOpt, Debug)]
 struct Add {
     #[structopt(subcommand)]
     subcmd: AddSubCommand,
@@ -85,6 +100,7 @@ async fn main() -> () {
             Ok(root) => {
                 init_config(root.config);
                 db::init().await.unwrap();
+                redis::init().await.unwrap();
                 if let Some(name) = &root.user {
                     login(&mut user, name).await.unwrap();
                 }
@@ -101,6 +117,7 @@ async fn main() -> () {
     } else {
         init_config(None);
         db::init().await.unwrap();
+        redis::init().await.unwrap();
     }
     let mut rl = Editor::<()>::new();
     let mut quit = false;
@@ -174,6 +191,30 @@ async fn handle_root(root: Root, user: &mut Option<User>) -> Fallible<bool> {
         Root::Quit => return Ok(true),
         Root::Add(add) => handle_add(add.subcmd, user).await?,
         Root::Migrate => migrate().await?,
+        Root::MigrateBlob(MigrateBlob { from, to, delete }) => {
+            if from == to {
+                return Err(Error::new_op("來源與目的地相同"));
+            }
+            let count = db::blob::migrate(
+                &*db::blob::new_store(from),
+                &*db::blob::new_store(to),
+                delete,
+            )
+            .await?;
+            println!("已搬移 {} 筆資料，記得修改設定檔的 blob_store", count);
+        }
+        Root::BackfillPersonalBoards => {
+            let count = db::board::backfill_personal().await?;
+            println!("已補建 {} 個個人看板", count);
+        }
+        Root::CheckForce => {
+            let broken = db::board::check_force().await?;
+            for (board_name, err) in broken.iter() {
+                println!("{}： {}", board_name, err);
+            }
+            println!("{} 個看板的力無法解析", broken.len());
+        }
+        Root::Jobs => show_jobs().await?,
         Root::List => {
             for db in list_db()? {
                 let prefix = if &db == db_name { "* " } else { "" };
@@ -184,6 +225,43 @@ async fn handle_root(root: Root, user: &mut Option<User>) -> Fallible<bool> {
     Ok(false)
 }
 
+async fn show_jobs() -> Fallible<()> {
+    if get_config().redis.store == KvStoreKind::Memory {
+        return Err(Error::new_op(
+            "短期資料存於伺服器記憶體，無法從 dbtool 查詢背景工作",
+        ));
+    }
+    for job in service::job::jobs() {
+        println!("{}（每 {} 秒）", job.name, job.interval());
+        let holder = service::job::get_lock_holder(job.name).await?;
+        println!("    執行者：{}", holder.as_deref().unwrap_or("無"));
+        let status = match service::job::get_status(job.name).await? {
+            Some(status) => status,
+            None => {
+                println!("    尚未執行過");
+                continue;
+            }
+        };
+        let fmt_time = |time: Option<chrono::DateTime<chrono::Utc>>| {
+            time.map_or("無".to_owned(), |t| t.to_rfc3339())
+        };
+        println!(
+            "    上次執行：{}（{}）",
+            fmt_time(status.last_run),
+            status.instance
+        );
+        println!("    上次成功：{}", fmt_time(status.last_success));
+        println!("    連續失敗：{} 次", status.failures);
+        if let Some(err) = status.last_error {
+            println!("    上次錯誤：{}", fmt_time(status.last_error_time));
+            for line in err.lines() {
+                println!("        {}", line);
+            }
+        }
+    }
+    Ok(())
+}
+
 async fn migrate() -> Fallible<()> {
     let conf = &get_config().database;
     let migrator = Migrator::new(std::path::Path::new("./migrations")).await?;
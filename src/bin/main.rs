@@ -1,5 +1,5 @@
 use carbonbond::{
-    config, custom_error::Fallible, db, redis, routes::get_routes, service::hot_boards, Ctx,
+    config, custom_error::Fallible, db, redis, routes::get_routes, service::job, Ctx,
 };
 
 #[tokio::main]
@@ -20,8 +20,7 @@ async fn main() -> Fallible<()> {
     log::info!("初始化資料庫連線池，位置：{}", &conf.database.get_url());
     db::init().await.unwrap();
 
-    // 初始化 redis
-    log::info!("初始化 redis 客戶端");
+    // 初始化短期資料存放處（redis 或記憶體）
     redis::init().await.unwrap();
 
     // 啓動伺服器
@@ -34,7 +33,7 @@ async fn main() -> Fallible<()> {
 
     tokio::select! {
         _ = web_service => {},
-        res = hot_boards::start() => { res?; },
+        _ = job::start() => {},
     };
 
     Ok(())
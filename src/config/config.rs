@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
@@ -30,7 +31,12 @@ pub struct RawConfig {
     pub server: RawServerConfig,
     pub user: RawUserConfig,
     pub database: DatabaseConfig,
+    #[serde(default)]
     pub redis: RedisConfig,
+    #[serde(default)]
+    pub party: PartyConfig,
+    #[serde(default)]
+    pub job: JobConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,6 +62,8 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub user: UserConfig,
     pub redis: RedisConfig,
+    pub party: PartyConfig,
+    pub job: JobConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -67,6 +75,39 @@ pub struct DatabaseConfig {
     pub host: String,
     pub data_path: String,
     pub max_conn: u32,
+    #[serde(default)]
+    pub blob_store: BlobStoreKind,
+    #[serde(default = "default_blob_path")]
+    pub blob_path: String,
+}
+fn default_blob_path() -> String {
+    "blobs".to_owned()
+}
+
+/// 圖片等二進位資料的存放處
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobStoreKind {
+    Postgres,
+    Fs,
+}
+impl Default for BlobStoreKind {
+    fn default() -> Self {
+        BlobStoreKind::Postgres
+    }
+}
+impl std::str::FromStr for BlobStoreKind {
+    type Err = Error;
+    fn from_str(s: &str) -> Fallible<Self> {
+        match s {
+            "postgres" => Ok(BlobStoreKind::Postgres),
+            "fs" => Ok(BlobStoreKind::Fs),
+            _ => Err(Error::new_op(format!(
+                "未知的存放處 {}，應爲 postgres 或 fs",
+                s
+            ))),
+        }
+    }
 }
 impl DatabaseConfig {
     pub fn get_url(&self) -> String {
@@ -76,10 +117,82 @@ impl DatabaseConfig {
         )
     }
 }
+/// 政黨鍵能的用途
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PartyConfig {
+    /// 創立看板時政黨需有的鍵能
+    #[serde(default)]
+    pub board_creation_energy: i32,
+    /// 創立看板時政黨需有的黨員數（含黨主席）
+    #[serde(default)]
+    pub board_creation_members: i64,
+    /// 政黨遷入其它看板時需有的鍵能
+    #[serde(default)]
+    pub relocation_energy: i32,
+    /// 政黨遷入其它看板時需有的黨員數（含黨主席）
+    #[serde(default)]
+    pub relocation_members: i64,
+    /// 開票時每點政黨鍵能折合的票數，0 表示只看票數
+    #[serde(default)]
+    pub election_energy_weight: f64,
+}
+/// 背景工作的排程
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobConfig {
+    /// 各工作的執行間隔（秒），未列出者使用預設值
+    #[serde(default)]
+    pub interval: HashMap<String, u64>,
+    /// 連續失敗時，重試間隔加倍的上限（秒）
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff: u64,
+}
+fn default_max_backoff() -> u64 {
+    600
+}
+impl Default for JobConfig {
+    fn default() -> Self {
+        JobConfig {
+            interval: HashMap::new(),
+            max_backoff: default_max_backoff(),
+        }
+    }
+}
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RedisConfig {
+    #[serde(default)]
+    pub store: KvStoreKind,
+    #[serde(default = "default_redis_host")]
     pub host: String,
 }
+fn default_redis_host() -> String {
+    "redis://127.0.0.1/".to_owned()
+}
+impl Default for RedisConfig {
+    fn default() -> Self {
+        RedisConfig {
+            store: KvStoreKind::default(),
+            host: default_redis_host(),
+        }
+    }
+}
+
+/// 登入狀態、看板人氣與熱門列表等短期資料的存放處
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KvStoreKind {
+    Redis,
+    /// 存於行程記憶體，重啓即消失，且無法在多個實例間共享
+    Memory,
+}
+impl Default for KvStoreKind {
+    fn default() -> Self {
+        if cfg!(feature = "redis") {
+            KvStoreKind::Redis
+        } else {
+            KvStoreKind::Memory
+        }
+    }
+}
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub address: String,
@@ -177,6 +290,14 @@ pub fn load_config(path: &Option<String>) -> Fallible<Config> {
     };
 
     let raw_config: RawConfig = toml::from_str(&content)?;
+    for (name, interval) in raw_config.job.interval.iter() {
+        if *interval < 1 {
+            return Err(Error::new_op(format!(
+                "背景工作 {} 的執行間隔需至少 1 秒",
+                name
+            )));
+        }
+    }
     let config = Config {
         mode,
         file_name,
@@ -184,6 +305,8 @@ pub fn load_config(path: &Option<String>) -> Fallible<Config> {
         user: Fallible::<UserConfig>::from(raw_config.user)?,
         database: raw_config.database,
         redis: raw_config.redis,
+        party: raw_config.party,
+        job: raw_config.job,
     };
 
     Ok(config)
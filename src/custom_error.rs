@@ -25,18 +25,28 @@ mod inner {
         StringField,
         #[display(fmt = "鍵結欄位")]
         BondField,
+        #[display(fmt = "投票欄位")]
+        PollField,
         #[display(fmt = "看板")]
         Board,
         #[display(fmt = "文章")]
         Article,
         #[display(fmt = "政黨")]
         Party,
+        #[display(fmt = "政黨成員")]
+        PartyMember,
         #[display(fmt = "使用者")]
         User,
         #[display(fmt = "通知")]
         Notification,
         #[display(fmt = "註冊碼")]
         SignupToken,
+        #[display(fmt = "圖片")]
+        Image,
+        #[display(fmt = "檢舉")]
+        Report,
+        #[display(fmt = "選舉")]
+        Election,
     }
 
     #[derive(Serialize, Display, Debug, TypeScriptify)]
@@ -46,6 +56,10 @@ mod inner {
         TargetNotSameBoard(i64),
         TargetViolateCategory,
         TargetViolateEnergy,
+        TargetLocked,
+        // 圖片欄位的錯誤也經由驗證器回報
+        ImageNotFound(i64),
+        ImageNotOwned(i64),
     }
     #[derive(Serialize, Display, Debug, TypeScriptify)]
     pub enum ErrorCode {
@@ -60,8 +74,29 @@ mod inner {
         #[display(fmt = "JSON 解析錯誤")]
         ParsingJson,
         #[display(fmt = "力語言驗證： {:?}", "_0")]
-        #[serde(serialize_with = "serialize_err")]
-        ForceValidate(ForceValidateError<BondError>),
+        ForceValidate(Vec<ForceValidateError<BondError>>),
+        #[display(fmt = "已是此政黨成員或已申請加入")]
+        AlreadyInParty,
+        #[display(fmt = "政黨鍵能不足，需達 {}", "_0")]
+        InsufficientPartyEnergy(i32),
+        #[display(fmt = "政黨黨員不足，需達 {} 人", "_0")]
+        InsufficientPartyMembers(i64),
+        #[display(fmt = "選舉不在投票期間")]
+        ElectionClosed,
+        #[display(fmt = "已被此看板禁言")]
+        BannedFromBoard,
+        #[display(fmt = "置頂文章最多 {} 篇", "_0")]
+        PinLimitExceeded(usize),
+        #[display(fmt = "不合法的圖片： {}", "_0")]
+        InvalidImage(String),
+        #[display(fmt = "投票已截止")]
+        PollClosed,
+        #[display(fmt = "不合法的投票： {}", "_0")]
+        InvalidVote(String),
+        #[display(fmt = "數值超出範圍： {}", "_0")]
+        OutOfRange(String),
+        #[display(fmt = "一次最多批次 {} 個請求", "_0")]
+        BatchTooLarge(usize),
         #[display(fmt = "後端尚未實作")]
         UnImplemented,
         #[display(fmt = "其它： {}", "_0")]
@@ -74,6 +109,30 @@ mod inner {
         pub fn to_err(self) -> Error {
             self.into()
         }
+        /// 穩定的機器可讀代碼，供前端與其它客戶端判斷錯誤種類，變體改名時不應更動
+        pub fn name(&self) -> &'static str {
+            match self {
+                ErrorCode::NeedLogin => "need_login",
+                ErrorCode::PermissionDenied => "permission_denied",
+                ErrorCode::NotFound(..) => "not_found",
+                ErrorCode::DuplicateRegister => "duplicate_register",
+                ErrorCode::ParsingJson => "parsing_json",
+                ErrorCode::ForceValidate(..) => "force_validate",
+                ErrorCode::AlreadyInParty => "already_in_party",
+                ErrorCode::InsufficientPartyEnergy(..) => "insufficient_party_energy",
+                ErrorCode::InsufficientPartyMembers(..) => "insufficient_party_members",
+                ErrorCode::ElectionClosed => "election_closed",
+                ErrorCode::BannedFromBoard => "banned_from_board",
+                ErrorCode::PinLimitExceeded(..) => "pin_limit_exceeded",
+                ErrorCode::InvalidImage(..) => "invalid_image",
+                ErrorCode::PollClosed => "poll_closed",
+                ErrorCode::InvalidVote(..) => "invalid_vote",
+                ErrorCode::OutOfRange(..) => "out_of_range",
+                ErrorCode::BatchTooLarge(..) => "batch_too_large",
+                ErrorCode::UnImplemented => "unimplemented",
+                ErrorCode::Other(..) => "other",
+            }
+        }
     }
     #[derive(Serialize, Debug, TypeScriptify)]
     pub enum Error {
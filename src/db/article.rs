@@ -12,6 +12,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 fn default<T: Default>() -> T {
     Default::default()
@@ -34,10 +35,11 @@ macro_rules! metas {
                 INNER JOIN users ON articles.author_id = users.id
                 INNER JOIN boards ON articles.board_id = boards.id
                 INNER JOIN categories ON articles.category_id = categories.id
-            WHERE ($1
+            WHERE NOT articles.hidden
+                AND (($1
                 AND NOT categories.families && $2)
                 OR (NOT $1
-                AND categories.families && $2))
+                AND categories.families && $2)))
             SELECT "
                 + $select
                 + " FROM metas "
@@ -64,6 +66,9 @@ macro_rules! to_meta {
             author_id: $data.author_id,
             author_name: $data.author_name,
             create_time: $data.create_time,
+            locked: $data.locked,
+            pinned: $data.pinned,
+            featured: $data.featured,
             digest: crate::api::model::ArticleDigest {
                 content: $data.digest,
                 truncated: $data.digest_truncated,
@@ -90,6 +95,7 @@ macro_rules! to_bond_and_meta {
                 to: $data.to,
                 energy: $data.bond_energy,
                 name: $data.name,
+                tag: $data.tag,
                 id: $data.bond_id,
             },
             to_meta!($data),
@@ -113,8 +119,8 @@ pub async fn metas_to_articles(
     let mut categories = Vec::new();
     let mut ids = Vec::new();
     for meta in &metas {
-        let category = get_force_category(meta.board_id, &meta.category_name).await?;
-        categories.push(category);
+        // 依文章發表時的分類版本解析，看板改過力語言也不受影響
+        categories.push(Arc::new(parse_category(&meta.category_source)?));
         ids.push(meta.id);
     }
     let contents = article_content::get_by_article_ids(ids, categories).await?;
@@ -230,9 +236,20 @@ pub async fn get_meta_by_id(id: i64) -> Fallible<ArticleMeta> {
     Ok(to_meta!(meta))
 }
 
+/// 依 `ids` 的順序回傳，隱藏或不存在的文章略過
+pub async fn get_meta_by_ids(ids: &[i64]) -> Fallible<Vec<ArticleMeta>> {
+    let pool = get_pool();
+    let metas = metas!("*", "WHERE id = ANY($3)", true, EMPTY_SET, ids)
+        .fetch_all(pool)
+        .await?;
+    let mut metas: Vec<_> = metas.into_iter().map(|d| to_meta!(d)).collect();
+    metas.sort_by_key(|m| ids.iter().position(|id| *id == m.id));
+    Ok(metas)
+}
+
 pub async fn get_by_id(id: i64) -> Fallible<Article> {
     let meta = get_meta_by_id(id).await?;
-    let category = get_force_category(meta.board_id, &meta.category_name).await?;
+    let category = parse_category(&meta.category_source)?;
     let content = article_content::get_by_article_id(meta.id, &category).await?;
     Ok(Article { meta, content })
 }
@@ -245,10 +262,30 @@ pub async fn get_by_board_name(
 ) -> Fallible<impl ExactSizeIterator<Item = ArticleMeta>> {
     let pool = get_pool();
     let family_filter = filter_tuple(family_filter);
-    let metas = metas!(
+    // 置頂文章只出現在第一頁的最前面，不佔 limit
+    let mut metas: Vec<ArticleMeta> = if max_id.is_none() {
+        metas!(
+            "*",
+            "
+            WHERE board_name = $3 AND pinned
+            ORDER BY pin_time DESC
+            ",
+            family_filter.0,
+            family_filter.1,
+            board_name
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|d| to_meta!(d))
+        .collect()
+    } else {
+        Vec::new()
+    };
+    let rest = metas!(
         "*",
         "
-        WHERE board_name = $3 AND ($5 OR id < $6)
+        WHERE board_name = $3 AND NOT pinned AND ($5 OR id < $6)
         ORDER BY create_time DESC
         LIMIT $4
         ",
@@ -261,6 +298,32 @@ pub async fn get_by_board_name(
     )
     .fetch_all(pool)
     .await?;
+    metas.extend(rest.into_iter().map(|d| to_meta!(d)));
+    Ok(metas.into_iter())
+}
+
+pub async fn get_featured(
+    board_id: i64,
+    max_id: Option<i64>,
+    limit: usize,
+) -> Fallible<impl ExactSizeIterator<Item = ArticleMeta>> {
+    let pool = get_pool();
+    let metas = metas!(
+        "*",
+        "
+        WHERE board_id = $3 AND featured AND ($5 OR id < $6)
+        ORDER BY create_time DESC
+        LIMIT $4
+        ",
+        true,
+        EMPTY_SET,
+        board_id,
+        limit as i64,
+        max_id.is_none(),
+        max_id.unwrap_or_default()
+    )
+    .fetch_all(pool)
+    .await?;
     Ok(metas.into_iter().map(|d| to_meta!(d)))
 }
 
@@ -268,6 +331,7 @@ pub async fn get_by_board_name(
 pub async fn get_bondee_meta(
     article_id: i64,
     category_set: Option<&[String]>,
+    tag_set: Option<&[String]>,
     family_filter: &FamilyFilter,
 ) -> Fallible<impl ExactSizeIterator<Item = (Edge, ArticleMeta)>> {
     let pool = get_pool();
@@ -275,19 +339,22 @@ pub async fn get_bondee_meta(
     let data = metas!(
         "
         DISTINCT metas.*, abf.article_id as from, abf.value as to,
-        abf.energy as bond_energy, abf.name, abf.id as bond_id
+        abf.energy as bond_energy, abf.name, abf.tag, abf.id as bond_id
         ",
         "
         INNER JOIN article_bond_fields abf on metas.id = abf.value
         WHERE abf.article_id = $3
         AND ($4 OR category_name = ANY($5))
+        AND ($6 OR abf.tag = ANY($7))
         ORDER BY create_time DESC
         ",
         family_filter.0,
         family_filter.1,
         article_id,
         category_set.is_none(),
-        category_set.unwrap_or(EMPTY_SET)
+        category_set.unwrap_or(EMPTY_SET),
+        tag_set.is_none(),
+        tag_set.unwrap_or(EMPTY_SET)
     )
     .fetch_all(pool)
     .await?;
@@ -298,6 +365,7 @@ pub async fn get_bondee_meta(
 pub async fn get_bonder_meta(
     article_id: i64,
     category_set: Option<&[String]>,
+    tag_set: Option<&[String]>,
     family_filter: &FamilyFilter,
 ) -> Fallible<impl ExactSizeIterator<Item = (Edge, ArticleMeta)>> {
     let pool = get_pool();
@@ -305,19 +373,22 @@ pub async fn get_bonder_meta(
     let data = metas!(
         "
         DISTINCT metas.*, abf.article_id as from, abf.value as to,
-        abf.energy as bond_energy, abf.name, abf.id as bond_id
+        abf.energy as bond_energy, abf.name, abf.tag, abf.id as bond_id
         ",
         "
         INNER JOIN article_bond_fields abf ON metas.id = abf.article_id
         WHERE abf.value = $3
         AND ($4 OR category_name = ANY($5))
+        AND ($6 OR abf.tag = ANY($7))
         ORDER BY create_time DESC
         ",
         family_filter.0,
         family_filter.1,
         article_id,
         category_set.is_none(),
-        category_set.unwrap_or(EMPTY_SET)
+        category_set.unwrap_or(EMPTY_SET),
+        tag_set.is_none(),
+        tag_set.unwrap_or(EMPTY_SET)
     )
     .fetch_all(pool)
     .await?;
@@ -327,9 +398,10 @@ pub async fn get_bonder_meta(
 pub async fn get_bonder(
     article_id: i64,
     category_set: Option<&[String]>,
+    tag_set: Option<&[String]>,
     family_filter: &FamilyFilter,
 ) -> Fallible<impl ExactSizeIterator<Item = (Edge, Article)>> {
-    let iter = get_bonder_meta(article_id, category_set, family_filter).await?;
+    let iter = get_bonder_meta(article_id, category_set, tag_set, family_filter).await?;
     let mut bonds = Vec::<Edge>::with_capacity(iter.len());
     let metas: Vec<_> = iter
         .map(|(bond, meta)| {
@@ -373,23 +445,35 @@ pub async fn get_newest_category(board_id: i64, category_name: &str) -> Fallible
 }
 
 lazy_static! {
-    static ref FORCE_CACHE: RwLock<HashMap<i64, Arc<force::Force>>> = RwLock::new(HashMap::new());
+    /// 看板 id 對應（快取時間, 力語言）
+    static ref FORCE_CACHE: RwLock<HashMap<i64, (Instant, Arc<force::Force>)>> =
+        RwLock::new(HashMap::new());
+}
+
+/// 本實例修改力語言時立即失效，其它實例的修改至多延遲這麼久才生效
+const FORCE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// 更新看板力語言後須呼叫
+pub(super) fn invalidate_force_cache(board_id: i64) {
+    FORCE_CACHE.write().unwrap().remove(&board_id);
 }
 
-// FIXME: 實作更新看板力語言的時候，更新資料庫的同時也必須更新快取
 async fn get_force(board_id: i64) -> Fallible<Arc<force::Force>> {
     let exist = {
         let cache = FORCE_CACHE.read().unwrap();
         match cache.get(&board_id) {
-            None => None,
-            Some(category) => Some(category.clone()),
+            Some((time, force)) if time.elapsed() < FORCE_CACHE_TTL => Some(force.clone()),
+            _ => None,
         }
     };
     match exist {
         Some(force) => Ok(force),
         None => {
             let force = Arc::new(force::parse(&board::get_by_id(board_id).await?.force)?);
-            FORCE_CACHE.write().unwrap().insert(board_id, force.clone());
+            FORCE_CACHE
+                .write()
+                .unwrap()
+                .insert(board_id, (Instant::now(), force.clone()));
             Ok(force)
         }
     }
@@ -415,14 +499,21 @@ pub async fn create(
     title: &str,
     content: String,
 ) -> Fallible<i64> {
-    let content: Value = serde_json::from_str(&content).map_err(|err| {
+    let mut content: Value = serde_json::from_str(&content).map_err(|err| {
         ErrorCode::ParsingJson
             .context("文章內容反序列化失敗")
             .context(err)
     })?;
 
+    if super::moderation::is_banned(board_id, author_id).await? {
+        return Err(ErrorCode::BannedFromBoard.into());
+    }
+    // 已自力語言移除的分類不能再發文
+    get_force_category(board_id, &category_name.to_owned()).await?;
     let category = get_newest_category(board_id, category_name).await?;
     let force_category = parse_category(&category.source)?;
+    // 未填寫的欄位以預設值補上，再交由驗證器檢查
+    force_category.fill_defaults(&mut content);
     let mut conn = get_pool().begin().await?;
     let article_id = sqlx::query!(
         "
@@ -443,6 +534,7 @@ pub async fn create(
         &mut conn,
         article_id,
         board_id,
+        author_id,
         Cow::Borrowed(&content),
         &force_category,
     )
@@ -106,8 +106,15 @@ fn insert_kvs<T: Insertable>(kvs: &mut HashMap<String, Value>, fields: &Vec<T>)
 fn init_kvs(category: &Category) -> HashMap<String, Value> {
     let mut kvs: HashMap<String, Value> = HashMap::new();
     for field in &category.fields {
-        if let force::DataType::Array { .. } = field.datatype {
-            kvs.insert(field.name.clone(), (Vec::new() as Vec<Value>).into());
+        match field.datatype {
+            force::DataType::Array { .. } => {
+                kvs.insert(field.name.clone(), (Vec::new() as Vec<Value>).into());
+            }
+            // 投票欄位不存於文章內容，票數見 ArticleStatistics
+            force::DataType::Single(force::BasicDataType::Poll(_)) => {
+                kvs.insert(field.name.clone(), Value::Null);
+            }
+            _ => (),
         }
     }
     kvs
@@ -236,11 +243,20 @@ pub async fn get_by_article_id(id: i64, category: &Category) -> Fallible<String>
 
 struct Validator {
     board_id: i64,
+    author_id: i64,
 }
 
 #[async_trait::async_trait]
 impl ValidatorTrait for Validator {
     type OtherError = BondError;
+    async fn validate_image(&self, image_id: i64) -> Result<(), Self::OtherError> {
+        match super::image::get_owner(image_id).await {
+            Ok(Some(Some(owner_id))) if owner_id == self.author_id => Ok(()),
+            Ok(Some(_)) => Err(BondError::ImageNotOwned(image_id)),
+            Ok(None) => Err(BondError::ImageNotFound(image_id)),
+            Err(e) => Err(BondError::Custom(Box::new(e))),
+        }
+    }
     async fn validate_bond(&self, bondee: &Bondee, data: &Bond) -> Result<(), Self::OtherError> {
         //XXX: 鍵能
         let meta = match super::article::get_meta_by_id(data.target_article).await {
@@ -258,6 +274,9 @@ impl ValidatorTrait for Validator {
         if meta.board_id != self.board_id {
             return Err(BondError::TargetNotSameBoard(meta.board_id));
         }
+        if meta.locked {
+            return Err(BondError::TargetLocked);
+        }
         // XXX: 鍵能錯誤
         match bondee {
             Bondee::All => Ok(()),
@@ -328,16 +347,18 @@ async fn insert_bond_field(
 ) -> Fallible<()> {
     sqlx::query!(
         "INSERT INTO article_bond_fields
-        (article_id, name, value, energy)
-        VALUES ($1, $2, $3, $4)",
+        (article_id, name, value, energy, tag)
+        VALUES ($1, $2, $3, $4, $5)",
         article_id,
         field_name,
         bond.target_article,
-        bond.energy
+        bond.energy,
+        bond.tag
     )
     .execute(&mut *conn)
     .await?;
     super::article::update_energy(conn, bond.target_article, bond.energy).await?;
+    super::party::transfer_energy(conn, bond.target_article, bond.energy).await?;
     Ok(())
 }
 async fn insert_field(
@@ -348,7 +369,7 @@ async fn insert_field(
 ) -> Fallible<()> {
     log::debug!("插入文章內容 {:?} {:?}", field, value);
     match field.datatype.basic_type() {
-        force::BasicDataType::Number => match value {
+        force::BasicDataType::Number | force::BasicDataType::Image => match value {
             Value::Number(number) => {
                 insert_int_field(conn, article_id, &field.name, number.as_i64().unwrap()).await?
             }
@@ -362,11 +383,13 @@ async fn insert_field(
                 _ => {}
             }
         }
-        force::BasicDataType::Bond(_) => match serde_json::from_value::<Bond>(value) {
+        force::BasicDataType::Bond(..) => match serde_json::from_value::<Bond>(value) {
             Ok(bond) => insert_bond_field(conn, article_id, &field.name, &bond).await?,
             // validate 過，不可能發生
             _ => {}
         },
+        // 選票另存於 poll_votes
+        force::BasicDataType::Poll(_) => {}
         _ => {
             return Err(
                 ErrorCode::Other(format!("力語言尚未支援 {:?} 型別", field.datatype)).into(),
@@ -380,17 +403,21 @@ pub(super) async fn create(
     conn: &mut PgConnection,
     article_id: i64,
     board_id: i64,
+    author_id: i64,
     content: Cow<'_, Value>,
     category: &Category,
 ) -> Fallible<()> {
     // 檢驗格式
-    let validator = Validator { board_id };
+    let validator = Validator {
+        board_id,
+        author_id,
+    };
     match validator
         .validate_category(&category, content.as_ref())
         .await
     {
         Ok(()) => (),
-        Err(err) => return Err(ErrorCode::ForceValidate(err).into()),
+        Err(errors) => return Err(ErrorCode::ForceValidate(errors).into()),
     }
 
     use force::DataType::*;
@@ -1,6 +1,7 @@
 use super::get_pool;
-use crate::api::model::{ArticleMeta, ArticlePersonalMeta};
+use crate::api::model::ArticleMeta;
 use crate::custom_error::Fallible;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
 struct Entry {
@@ -21,7 +22,7 @@ pub async fn get(metas: Vec<&mut ArticleMeta>) -> Fallible {
         FROM article_bond_fields abf
             INNER JOIN articles ON articles.id = abf.article_id
             INNER JOIN categories ON categories.id = articles.category_id
-        WHERE abf.value = ANY($1))
+        WHERE abf.value = ANY($1) AND NOT articles.hidden)
         SELECT id, COUNT(DISTINCT article_id) as "count!", is_satellite as "is_satellite!" FROM replies
         GROUP BY id, is_satellite
         "#,
@@ -29,6 +30,7 @@ pub async fn get(metas: Vec<&mut ArticleMeta>) -> Fallible {
     )
     .fetch_all(pool)
     .await?;
+    let mut polls = super::poll::get_tallies(&ids).await?;
     let mut map: HashMap<_, _> = metas.into_iter().map(|meta| (meta.id, meta)).collect();
     for r in replies.into_iter() {
         if let Some(a) = map.get_mut(&r.id) {
@@ -39,6 +41,11 @@ pub async fn get(metas: Vec<&mut ArticleMeta>) -> Fallible {
             }
         }
     }
+    for (id, a) in map.iter_mut() {
+        if let Some(tallies) = polls.remove(id) {
+            a.stat.polls = tallies;
+        }
+    }
     Ok(())
 }
 
@@ -53,11 +60,65 @@ pub async fn get_personal(metas: Vec<&mut ArticleMeta>, user_id: i64) -> Fallibl
     )
     .fetch_all(pool)
     .await?;
+    let mut votes = super::poll::get_votes(&ids, user_id).await?;
     let mut map: HashMap<_, _> = metas.into_iter().map(|meta| (meta.id, meta)).collect();
     for p in personals.into_iter() {
         if let Some(a) = map.get_mut(&p.article_id) {
-            a.personal_meta = ArticlePersonalMeta { is_favorite: true };
+            a.personal_meta.is_favorite = true;
+        }
+    }
+    for (id, a) in map.iter_mut() {
+        if let Some(v) = votes.remove(id) {
+            a.personal_meta.votes = v;
         }
     }
     Ok(())
 }
+
+pub struct HotCandidate {
+    pub id: i64,
+    pub board_id: i64,
+    pub energy: i32,
+    pub replies: i64,
+    pub satellite_replies: i64,
+    pub favorites: i64,
+    pub create_time: DateTime<Utc>,
+}
+
+/// `since` 之後發表、未隱藏的文章及其統計，供熱門文章評分
+pub async fn get_hot_candidates(since: DateTime<Utc>) -> Fallible<Vec<HotCandidate>> {
+    let pool = get_pool();
+    let candidates = sqlx::query_as!(
+        HotCandidate,
+        r#"
+        WITH candidates AS (
+            SELECT id, board_id, energy, create_time FROM articles
+            WHERE create_time > $1 AND NOT hidden
+        ), replies AS (
+            SELECT abf.value AS id,
+                COUNT(DISTINCT abf.article_id) FILTER (WHERE NOT categories.families && '{衛星}') AS replies,
+                COUNT(DISTINCT abf.article_id) FILTER (WHERE categories.families && '{衛星}') AS satellite_replies
+            FROM article_bond_fields abf
+            INNER JOIN articles ON articles.id = abf.article_id
+            INNER JOIN categories ON categories.id = articles.category_id
+            WHERE abf.value IN (SELECT id FROM candidates) AND NOT articles.hidden
+            GROUP BY abf.value
+        ), favorites AS (
+            SELECT article_id AS id, COUNT(*) AS favorites FROM favorite_articles
+            WHERE article_id IN (SELECT id FROM candidates)
+            GROUP BY article_id
+        )
+        SELECT candidates.id, candidates.board_id, candidates.energy, candidates.create_time,
+            COALESCE(replies.replies, 0) AS "replies!",
+            COALESCE(replies.satellite_replies, 0) AS "satellite_replies!",
+            COALESCE(favorites.favorites, 0) AS "favorites!"
+        FROM candidates
+        LEFT JOIN replies ON replies.id = candidates.id
+        LEFT JOIN favorites ON favorites.id = candidates.id
+        "#,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
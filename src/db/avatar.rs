@@ -1,16 +1,19 @@
-use super::get_pool;
-use crate::custom_error::Fallible;
+use super::{blob::get_store, get_pool, image::Image};
+use crate::custom_error::{ErrorCode, Fallible};
+use crate::util::avatar::process;
 use base64::decode;
 
 struct IDWrap {
     id: i64,
 }
 
-pub async fn get_avatar(user_name: &str) -> Fallible<hyper::Body> {
+/// 使用者未設定大頭貼時爲 None
+pub async fn get_avatar(user_name: &str) -> Fallible<Option<Image>> {
     let pool = get_pool();
-    let image = sqlx::query!(
+    let image = sqlx::query_as!(
+        Image,
         "
-        SELECT images.raw_data
+        SELECT images.blob_key, images.content_type
         FROM images
         INNER JOIN users
         ON users.avatar = images.id
@@ -21,12 +24,7 @@ pub async fn get_avatar(user_name: &str) -> Fallible<hyper::Body> {
     .fetch_optional(pool)
     .await?;
 
-    if let Some(image) = image {
-        Ok(hyper::Body::from(image.raw_data))
-    } else {
-        let image = std::fs::read("./assets/no-avatar.png").expect("讀取預設大頭貼失敗");
-        Ok(hyper::Body::from(image))
-    }
+    Ok(image)
 }
 
 pub async fn update_avatar(user_id: i64, image: String) -> Fallible<()> {
@@ -44,14 +42,17 @@ pub async fn update_avatar(user_id: i64, image: String) -> Fallible<()> {
     .fetch_optional(pool)
     .await?;
 
-    let img = decode(image)?;
+    let img = decode(image)
+        .map_err(|e| ErrorCode::InvalidImage("base64 解碼失敗".to_owned()).context(e))?;
+    let img = tokio::task::spawn_blocking(move || process(&img)).await??;
+    let blob_key = get_store().put(&img).await?;
 
     if let Some(IDWrap { id }) = image_id {
         sqlx::query!(
             "UPDATE images
-            SET raw_data = $1
+            SET blob_key = $1, content_type = 'image/png'
             WHERE images.id = $2",
-            img,
+            blob_key,
             id
         )
         .execute(pool)
@@ -60,11 +61,11 @@ pub async fn update_avatar(user_id: i64, image: String) -> Fallible<()> {
         let id = sqlx::query_as!(
             IDWrap,
             "
-            INSERT INTO images (raw_data)
-            VALUES ($1)
+            INSERT INTO images (blob_key, content_type)
+            VALUES ($1, 'image/png')
             RETURNING images.id
             ",
-            img
+            blob_key
         )
         .fetch_one(pool)
         .await?
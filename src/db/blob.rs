@@ -0,0 +1,166 @@
+use super::get_pool;
+use crate::config::{get_config, BlobStoreKind};
+use crate::custom_error::{DataType, ErrorCode, Fallible};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use state::Storage;
+use std::path::PathBuf;
+
+static BLOB_STORE: Storage<Box<dyn BlobStore>> = Storage::new();
+
+/// 以內容雜湊爲鍵的二進位資料存放處，同樣的內容只存一份
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// 存入資料，回傳其鍵
+    async fn put(&self, data: &[u8]) -> Fallible<String>;
+    async fn get(&self, key: &str) -> Fallible<Vec<u8>>;
+    async fn delete(&self, key: &str) -> Fallible;
+    /// 預設一次讀完再回傳，能串流的實作應覆寫之
+    async fn stream(&self, key: &str) -> Fallible<hyper::Body> {
+        Ok(hyper::Body::from(self.get(key).await?))
+    }
+}
+
+pub fn hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn not_found(key: &str) -> crate::custom_error::Error {
+    ErrorCode::NotFound(DataType::Image, key.to_owned()).into()
+}
+
+/// 存於資料庫的 blobs 表
+pub struct PgBlobStore;
+
+#[async_trait]
+impl BlobStore for PgBlobStore {
+    async fn put(&self, data: &[u8]) -> Fallible<String> {
+        let pool = get_pool();
+        let key = hash(data);
+        sqlx::query!(
+            "INSERT INTO blobs (key, data) VALUES ($1, $2) ON CONFLICT (key) DO NOTHING",
+            key,
+            data
+        )
+        .execute(pool)
+        .await?;
+        Ok(key)
+    }
+    async fn get(&self, key: &str) -> Fallible<Vec<u8>> {
+        let pool = get_pool();
+        let blob = sqlx::query!("SELECT data FROM blobs WHERE key = $1", key)
+            .fetch_optional(pool)
+            .await?;
+        blob.map(|blob| blob.data).ok_or_else(|| not_found(key))
+    }
+    async fn delete(&self, key: &str) -> Fallible {
+        let pool = get_pool();
+        sqlx::query!("DELETE FROM blobs WHERE key = $1", key)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// 存於本地檔案系統，以鍵的前兩碼分目錄
+pub struct FsBlobStore {
+    root: PathBuf,
+}
+
+impl FsBlobStore {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        FsBlobStore { root: root.into() }
+    }
+    fn path(&self, key: &str) -> Fallible<PathBuf> {
+        // 鍵由雜湊而來，擋掉其它字元以免跳出根目錄
+        if key.len() < 3 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(not_found(key));
+        }
+        Ok(self.root.join(&key[0..2]).join(key))
+    }
+    async fn open(&self, key: &str) -> Fallible<tokio::fs::File> {
+        match tokio::fs::File::open(self.path(key)?).await {
+            Ok(file) => Ok(file),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(not_found(key)),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStore for FsBlobStore {
+    async fn put(&self, data: &[u8]) -> Fallible<String> {
+        let key = hash(data);
+        let path = self.path(&key)?;
+        if tokio::fs::metadata(&path).await.is_ok() {
+            return Ok(key);
+        }
+        let dir = path.parent().unwrap();
+        tokio::fs::create_dir_all(dir).await?;
+        // 先寫入暫存檔再改名，避免讀到寫了一半的檔案。
+        // 同樣內容可能同時上傳，暫存檔名需各自不同；改名會覆蓋，而內容本就相同
+        let tmp = dir.join(format!(
+            "{}.{}.{:08x}.tmp",
+            key,
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        tokio::fs::write(&tmp, data).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(key)
+    }
+    async fn get(&self, key: &str) -> Fallible<Vec<u8>> {
+        use tokio::io::AsyncReadExt;
+        let mut data = Vec::new();
+        self.open(key).await?.read_to_end(&mut data).await?;
+        Ok(data)
+    }
+    async fn delete(&self, key: &str) -> Fallible {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+    async fn stream(&self, key: &str) -> Fallible<hyper::Body> {
+        let file = self.open(key).await?;
+        Ok(hyper::Body::wrap_stream(tokio_util::io::ReaderStream::new(
+            file,
+        )))
+    }
+}
+
+pub fn new_store(kind: BlobStoreKind) -> Box<dyn BlobStore> {
+    match kind {
+        BlobStoreKind::Postgres => Box::new(PgBlobStore),
+        BlobStoreKind::Fs => Box::new(FsBlobStore::new(&get_config().database.blob_path)),
+    }
+}
+
+pub fn init() {
+    let kind = get_config().database.blob_store;
+    log::info!("二進位資料存放於 {:?}", kind);
+    assert!(BLOB_STORE.set(new_store(kind)), "BlobStore 被重複創建");
+}
+
+pub fn get_store() -> &'static dyn BlobStore {
+    &**BLOB_STORE.get()
+}
+
+/// 把圖片用到的資料從一處搬到另一處，回傳搬動的筆數
+pub async fn migrate(from: &dyn BlobStore, to: &dyn BlobStore, delete: bool) -> Fallible<usize> {
+    let pool = get_pool();
+    let keys = sqlx::query!("SELECT DISTINCT blob_key FROM images")
+        .fetch_all(pool)
+        .await?;
+    for record in keys.iter() {
+        let data = from.get(&record.blob_key).await?;
+        let key = to.put(&data).await?;
+        if key != record.blob_key {
+            return Err(ErrorCode::Other(format!("{} 的內容與雜湊不符", record.blob_key)).into());
+        }
+        if delete {
+            from.delete(&record.blob_key).await?;
+        }
+    }
+    Ok(keys.len())
+}
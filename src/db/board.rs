@@ -1,7 +1,9 @@
-use super::{get_pool, DBObject, ToFallible};
-use crate::api::model::{Board, BoardName, BoardOverview, NewBoard};
-use crate::custom_error::{DataType, Error, Fallible};
+use super::{get_pool, DBObject, ToFallible, ToTypedFallible};
+use crate::api::model::{Board, BoardHistory, BoardName, BoardOverview, BoardType, NewBoard};
+use crate::config::get_config;
+use crate::custom_error::{DataType, Error, ErrorCode, Fallible};
 use force::parser::parse;
+use sqlx::PgConnection;
 
 impl DBObject for Board {
     const TYPE: DataType = DataType::Board;
@@ -71,7 +73,7 @@ pub async fn create(board: &NewBoard) -> Fallible<i64> {
     let mut conn = get_pool().begin().await?;
     let force = parse(&board.force)?;
     let prev_board_id = sqlx::query!(
-        "SELECT board_id FROM parties where id = $1",
+        "SELECT board_id FROM parties where id = $1 FOR UPDATE",
         board.ruling_party_id
     )
     .fetch_one(&mut conn)
@@ -100,27 +102,100 @@ pub async fn create(board: &NewBoard) -> Fallible<i64> {
     .fetch_one(&mut conn)
     .await?
     .id;
-    for (name, category) in &force.categories {
-        let _category_id = sqlx::query!(
-            "
-        INSERT INTO categories (board_id, category_name, version, source, families)
-        VALUES ($1, $2, 1, $3, $4)
-        RETURNING id
-        ",
-            board_id,
-            name,
-            category.source,
-            &category.family
-        )
-        .fetch_one(&mut conn)
-        .await?
-        .id;
-    }
+    update_categories(&mut conn, board_id, &force).await?;
     super::party::change_board(&mut conn, board.ruling_party_id, board_id).await?;
     conn.commit().await?;
     Ok(board_id)
 }
 
+/// 個人看板的預設力語言
+pub const PERSONAL_FORCE: &str = "
+日記 {
+    文本 內文
+}
+留言 @ [衛星] {
+    鍵結[*] 本體
+    文本 內文
+}";
+
+/// 個人看板與使用者同名，由擁有者獨自管理
+pub async fn create_personal(
+    conn: &mut PgConnection,
+    user_id: i64,
+    user_name: &str,
+) -> Fallible<i64> {
+    let force = parse(PERSONAL_FORCE)?;
+    let board_id = sqlx::query!(
+        "
+        INSERT INTO boards (board_name, board_type, title, force, ruling_party_id, owner_id)
+        VALUES ($1, $2, $3, $4, -1, $5)
+        RETURNING id
+        ",
+        user_name,
+        BoardType::Personal.to_string(),
+        format!("{} 的個人看板", user_name),
+        PERSONAL_FORCE,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await?
+    .id;
+    update_categories(conn, board_id, &force).await?;
+    Ok(board_id)
+}
+
+pub async fn get_personal(user_name: &str) -> Fallible<Board> {
+    get_by_name(user_name, &BoardType::Personal.to_string()).await
+}
+
+/// 爲尚無個人看板的使用者補建，回傳補建的數量
+pub async fn backfill_personal() -> Fallible<usize> {
+    let pool = get_pool();
+    let users = sqlx::query!(
+        "
+        SELECT users.id, users.user_name FROM users
+        WHERE NOT EXISTS (SELECT 1 FROM boards WHERE boards.owner_id = users.id)
+        "
+    )
+    .fetch_all(pool)
+    .await?;
+    for user in users.iter() {
+        let mut conn = pool.begin().await?;
+        create_personal(&mut conn, user.id, &user.user_name).await?;
+        conn.commit().await?;
+    }
+    Ok(users.len())
+}
+
+/// 以目前的語法重新解析所有看板的力，回傳無法解析者的看板名與錯誤
+///
+/// 力的語法改變後（如新增保留字元），舊有看板可能無法再解析，應於更新前檢查
+pub async fn check_force() -> Fallible<Vec<(String, String)>> {
+    let mut broken = Vec::new();
+    for board in get_all().await? {
+        if let Err(err) = parse(&board.force) {
+            broken.push((board.board_name, err.to_string()));
+        }
+    }
+    Ok(broken)
+}
+
+/// 由流亡政黨的黨主席創立看板，該黨成爲執政黨
+pub async fn found(founder_id: i64, board: &NewBoard) -> Fallible<i64> {
+    let config = &get_config().party;
+    super::party::check_eligibility(
+        founder_id,
+        board.ruling_party_id,
+        config.board_creation_members,
+        config.board_creation_energy,
+    )
+    .await?;
+    if !super::party::is_exiled(board.ruling_party_id).await? {
+        return Err(ErrorCode::PermissionDenied.context("僅流亡政黨可創立看板"));
+    }
+    create(board).await
+}
+
 pub async fn get_category_by_id(id: i64) -> Fallible<String> {
     let pool = get_pool();
     let category_str = sqlx::query!("SELECT source FROM categories WHERE id = $1", id)
@@ -129,3 +204,151 @@ pub async fn get_category_by_id(id: i64) -> Fallible<String> {
         .source;
     Ok(category_str)
 }
+
+/// 內容有變的分類才新增一個版本，舊文章仍照其版本顯示
+async fn update_categories(
+    conn: &mut PgConnection,
+    board_id: i64,
+    force: &force::Force,
+) -> Fallible {
+    for (name, category) in &force.categories {
+        let newest = sqlx::query!(
+            "
+            SELECT version, source FROM categories
+            WHERE board_id = $1 AND category_name = $2
+            ORDER BY version DESC
+            LIMIT 1
+            ",
+            board_id,
+            name
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        let version = match newest {
+            Some(newest) if newest.source == category.source => continue,
+            Some(newest) => newest.version + 1,
+            None => 1,
+        };
+        sqlx::query!(
+            "
+            INSERT INTO categories (board_id, category_name, version, source, families)
+            VALUES ($1, $2, $3, $4, $5)
+            ",
+            board_id,
+            name,
+            version,
+            category.source,
+            &category.family
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// 未給的欄位維持原樣
+pub async fn update(
+    editor_id: i64,
+    board_id: i64,
+    title: Option<String>,
+    detail: Option<String>,
+    force: Option<String>,
+) -> Fallible {
+    super::moderation::check_moderator(board_id, editor_id).await?;
+    let mut conn = get_pool().begin().await?;
+    let board = sqlx::query!(
+        "SELECT title, detail, force FROM boards WHERE id = $1 FOR UPDATE",
+        board_id
+    )
+    .fetch_one(&mut conn)
+    .await
+    .to_typed_fallible(DataType::Board, board_id)?;
+    let title = title.unwrap_or(board.title);
+    let detail = detail.unwrap_or(board.detail);
+    if let Some(force) = &force {
+        update_categories(&mut conn, board_id, &parse(force)?).await?;
+    }
+    let force = force.unwrap_or(board.force);
+    sqlx::query!(
+        "UPDATE boards SET title = $1, detail = $2, force = $3 WHERE id = $4",
+        title,
+        detail,
+        force,
+        board_id
+    )
+    .execute(&mut conn)
+    .await?;
+    sqlx::query!(
+        "
+        INSERT INTO board_histories (board_id, editor_id, title, detail, force)
+        VALUES ($1, $2, $3, $4, $5)
+        ",
+        board_id,
+        editor_id,
+        title,
+        detail,
+        force
+    )
+    .execute(&mut conn)
+    .await?;
+    conn.commit().await?;
+    super::article::invalidate_force_cache(board_id);
+    Ok(())
+}
+
+pub async fn get_history(board_id: i64) -> Fallible<Vec<BoardHistory>> {
+    let pool = get_pool();
+    let histories = sqlx::query_as!(
+        BoardHistory,
+        "
+        SELECT board_histories.id, editor_id, users.user_name AS editor_name,
+            title, detail, force, board_histories.create_time
+        FROM board_histories
+        INNER JOIN users ON users.id = board_histories.editor_id
+        WHERE board_id = $1
+        ORDER BY board_histories.create_time DESC
+        ",
+        board_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(histories)
+}
+
+pub struct BoardActivity {
+    pub board_id: i64,
+    pub posts: f64,
+    pub bonds: f64,
+}
+
+/// 近期發文數與被鍵結數，各依其時間以 e^(-經過秒數 / tau) 衰減後加總
+pub async fn get_decayed_activity(tau: f64) -> Fallible<Vec<BoardActivity>> {
+    let pool = get_pool();
+    // 超過五倍 tau 的權重已不到 1%，不必計入
+    let activities = sqlx::query_as!(
+        BoardActivity,
+        r#"
+        WITH posts AS (
+            SELECT board_id, SUM(EXP(-EXTRACT(EPOCH FROM NOW() - create_time)::float8 / $1)) AS score
+            FROM articles
+            WHERE create_time > NOW() - make_interval(secs => $1 * 5) AND NOT hidden
+            GROUP BY board_id
+        ), bonds AS (
+            SELECT targets.board_id,
+                SUM(EXP(-EXTRACT(EPOCH FROM NOW() - articles.create_time)::float8 / $1)) AS score
+            FROM article_bond_fields abf
+            INNER JOIN articles ON articles.id = abf.article_id
+            INNER JOIN articles targets ON targets.id = abf.value
+            WHERE articles.create_time > NOW() - make_interval(secs => $1 * 5)
+            GROUP BY targets.board_id
+        )
+        SELECT COALESCE(posts.board_id, bonds.board_id) AS "board_id!",
+            COALESCE(posts.score, 0) AS "posts!", COALESCE(bonds.score, 0) AS "bonds!"
+        FROM posts FULL OUTER JOIN bonds ON posts.board_id = bonds.board_id
+        "#,
+        tau
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(activities)
+}
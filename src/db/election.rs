@@ -0,0 +1,310 @@
+use super::{get_pool, ToTypedFallible};
+use crate::api::model::{Election, ElectionCandidate, PartyPosition};
+use crate::custom_error::{DataType, ErrorCode, Fallible};
+use chrono::{DateTime, Utc};
+
+/// 投票者的鍵能下限
+pub const MIN_VOTER_ENERGY: i64 = 10;
+
+/// 看板最近一場選舉，尚未開票者優先
+pub async fn get_latest(board_id: i64, user_id: Option<i64>) -> Fallible<Option<Election>> {
+    let pool = get_pool();
+    let election = sqlx::query!(
+        "
+        SELECT id, board_id, start_time, end_time, tallied, winner_party_id FROM elections
+        WHERE board_id = $1
+        ORDER BY tallied, end_time DESC
+        LIMIT 1
+        ",
+        board_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    let election = match election {
+        Some(election) => election,
+        None => return Ok(None),
+    };
+    let candidates = sqlx::query!(
+        r#"
+        SELECT c.party_id, parties.party_name,
+            (SELECT COUNT(*) FROM election_votes v
+             WHERE v.election_id = c.election_id AND v.party_id = c.party_id) AS "votes!"
+        FROM election_candidates c
+        INNER JOIN parties ON parties.id = c.party_id
+        WHERE c.election_id = $1
+        ORDER BY c.create_time
+        "#,
+        election.id
+    )
+    .fetch_all(pool)
+    .await?;
+    let my_vote = match user_id {
+        Some(user_id) => sqlx::query!(
+            "SELECT party_id FROM election_votes WHERE election_id = $1 AND voter_id = $2",
+            election.id,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .map(|v| v.party_id),
+        None => None,
+    };
+    Ok(Some(Election {
+        id: election.id,
+        board_id: election.board_id,
+        start_time: election.start_time,
+        end_time: election.end_time,
+        tallied: election.tallied,
+        winner_party_id: election.winner_party_id,
+        candidates: candidates
+            .into_iter()
+            .map(|c| ElectionCandidate {
+                party_id: c.party_id,
+                party_name: c.party_name,
+                votes: if election.tallied {
+                    Some(c.votes)
+                } else {
+                    None
+                },
+            })
+            .collect(),
+        my_vote,
+    }))
+}
+
+/// 爲距上次選舉（或創板）已超過 `since` 的一般看板舉辦新選舉，執政黨自動參選
+pub async fn open_due(since: DateTime<Utc>, end_time: DateTime<Utc>) -> Fallible<Vec<i64>> {
+    let pool = get_pool();
+    let elections = sqlx::query!(
+        "
+        WITH new_elections AS (
+            INSERT INTO elections (board_id, end_time)
+            SELECT boards.id, $1 FROM boards
+            WHERE boards.board_type = 'general' AND boards.create_time < $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM elections
+                    WHERE elections.board_id = boards.id AND (NOT tallied OR end_time > $2)
+                )
+            RETURNING id, board_id
+        )
+        INSERT INTO election_candidates (election_id, party_id)
+        SELECT new_elections.id, boards.ruling_party_id FROM new_elections
+        INNER JOIN boards ON boards.id = new_elections.board_id
+        RETURNING election_id
+        ",
+        end_time,
+        since
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(elections.into_iter().map(|e| e.election_id).collect())
+}
+
+/// 投票期已結束但尚未開票的選舉
+pub async fn get_due() -> Fallible<Vec<i64>> {
+    let pool = get_pool();
+    let elections =
+        sqlx::query!("SELECT id FROM elections WHERE NOT tallied AND end_time <= NOW()")
+            .fetch_all(pool)
+            .await?;
+    Ok(elections.into_iter().map(|e| e.id).collect())
+}
+
+async fn get_open_board(election_id: i64) -> Fallible<i64> {
+    let pool = get_pool();
+    let election = sqlx::query!(
+        "SELECT board_id, tallied, end_time FROM elections WHERE id = $1",
+        election_id
+    )
+    .fetch_one(pool)
+    .await
+    .to_typed_fallible(DataType::Election, election_id)?;
+    if election.tallied || election.end_time <= Utc::now() {
+        return Err(ErrorCode::ElectionClosed.into());
+    }
+    Ok(election.board_id)
+}
+
+/// 看板上的政黨由黨主席登記參選
+pub async fn register(operator_id: i64, election_id: i64, party_id: i64) -> Fallible {
+    let board_id = get_open_board(election_id).await?;
+    let party = sqlx::query!("SELECT board_id FROM parties WHERE id = $1", party_id)
+        .fetch_one(get_pool())
+        .await
+        .to_typed_fallible(DataType::Party, party_id)?;
+    if party.board_id != Some(board_id) {
+        return Err(ErrorCode::PermissionDenied.context("僅此看板上的政黨可參選"));
+    }
+    if super::party::get_position(party_id, operator_id).await? != Some(PartyPosition::Chairman) {
+        return Err(ErrorCode::PermissionDenied.context("僅黨主席可登記參選"));
+    }
+    sqlx::query!(
+        "
+        INSERT INTO election_candidates (election_id, party_id) VALUES ($1, $2)
+        ON CONFLICT (election_id, party_id) DO NOTHING
+        ",
+        election_id,
+        party_id
+    )
+    .execute(get_pool())
+    .await?;
+    Ok(())
+}
+
+/// 訂閱者或看板政黨成員，且鍵能足夠者才能投票
+pub async fn is_eligible_voter(board_id: i64, user_id: i64) -> Fallible<bool> {
+    let pool = get_pool();
+    let record = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM users
+            WHERE users.id = $2 AND users.energy >= $3 AND (
+                EXISTS (
+                    SELECT 1 FROM subscribed_boards
+                    WHERE subscribed_boards.board_id = $1 AND subscribed_boards.user_id = $2
+                ) OR EXISTS (
+                    SELECT 1 FROM party_members
+                    INNER JOIN parties ON parties.id = party_members.party_id
+                    WHERE parties.board_id = $1 AND party_members.user_id = $2
+                        AND party_members.position > 0
+                )
+            )
+        ) AS "exists!"
+        "#,
+        board_id,
+        user_id,
+        MIN_VOTER_ENERGY
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(record.exists)
+}
+
+/// 重複投票會改投
+pub async fn vote(voter_id: i64, election_id: i64, party_id: i64) -> Fallible {
+    let board_id = get_open_board(election_id).await?;
+    if !is_eligible_voter(board_id, voter_id).await? {
+        return Err(ErrorCode::PermissionDenied.context(format!(
+            "需訂閱看板或爲看板政黨成員，且鍵能不少於 {}",
+            MIN_VOTER_ENERGY
+        )));
+    }
+    let pool = get_pool();
+    sqlx::query!(
+        "SELECT id FROM election_candidates WHERE election_id = $1 AND party_id = $2",
+        election_id,
+        party_id
+    )
+    .fetch_one(pool)
+    .await
+    .to_typed_fallible(DataType::Party, party_id)?;
+    sqlx::query!(
+        "
+        INSERT INTO election_votes (election_id, voter_id, party_id) VALUES ($1, $2, $3)
+        ON CONFLICT (election_id, voter_id) DO UPDATE SET party_id = $3, create_time = NOW()
+        ",
+        election_id,
+        voter_id,
+        party_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub struct TallyResult {
+    pub board_id: i64,
+    pub winner_party_id: i64,
+    pub changed: bool,
+    /// 投票者與參選政黨的成員
+    pub recipients: Vec<i64>,
+}
+
+/// 開票並移交執政權，已開過票則回傳 None
+///
+/// 得分爲票數加上政黨鍵能乘以 `energy_weight`。同分時現任執政黨優先，其次爲較早登記者；
+/// 無人投票則由現任續任
+pub async fn tally(election_id: i64, energy_weight: f64) -> Fallible<Option<TallyResult>> {
+    let mut conn = get_pool().begin().await?;
+    let election = sqlx::query!(
+        "
+        SELECT elections.board_id, boards.ruling_party_id FROM elections
+        INNER JOIN boards ON boards.id = elections.board_id
+        WHERE elections.id = $1 AND NOT elections.tallied
+        FOR UPDATE OF elections, boards
+        ",
+        election_id
+    )
+    .fetch_optional(&mut conn)
+    .await?;
+    let election = match election {
+        Some(election) => election,
+        None => return Ok(None),
+    };
+    let winner = sqlx::query!(
+        r#"
+        SELECT c.party_id FROM election_candidates c
+        INNER JOIN parties ON parties.id = c.party_id
+        LEFT JOIN election_votes v
+            ON v.election_id = c.election_id AND v.party_id = c.party_id
+        WHERE c.election_id = $1 AND parties.board_id = $2
+        GROUP BY c.party_id, c.create_time, parties.energy
+        ORDER BY COUNT(v.id)::float8 + $4::float8 * parties.energy DESC, c.party_id = $3 DESC, c.create_time
+        LIMIT 1
+        "#,
+        election_id,
+        election.board_id,
+        election.ruling_party_id,
+        energy_weight
+    )
+    .fetch_optional(&mut conn)
+    .await?
+    .map(|w| w.party_id)
+    .unwrap_or(election.ruling_party_id);
+    let changed = winner != election.ruling_party_id;
+    if changed {
+        // 先卸任再就任，check_board 觸發器要求新執政黨存在
+        sqlx::query!(
+            "UPDATE parties SET ruling = false WHERE board_id = $1 AND ruling",
+            election.board_id
+        )
+        .execute(&mut conn)
+        .await?;
+        sqlx::query!("UPDATE parties SET ruling = true WHERE id = $1", winner)
+            .execute(&mut conn)
+            .await?;
+        sqlx::query!(
+            "UPDATE boards SET ruling_party_id = $1 WHERE id = $2",
+            winner,
+            election.board_id
+        )
+        .execute(&mut conn)
+        .await?;
+    }
+    sqlx::query!(
+        "UPDATE elections SET tallied = true, winner_party_id = $1 WHERE id = $2",
+        winner,
+        election_id
+    )
+    .execute(&mut conn)
+    .await?;
+    let recipients = sqlx::query!(
+        r#"
+        SELECT voter_id AS "user_id!" FROM election_votes WHERE election_id = $1
+        UNION
+        SELECT party_members.user_id FROM party_members
+        INNER JOIN election_candidates c ON c.party_id = party_members.party_id
+        WHERE c.election_id = $1 AND party_members.position > 0
+        "#,
+        election_id
+    )
+    .fetch_all(&mut conn)
+    .await?;
+    conn.commit().await?;
+    Ok(Some(TallyResult {
+        board_id: election.board_id,
+        winner_party_id: winner,
+        changed,
+        recipients: recipients.into_iter().map(|r| r.user_id).collect(),
+    }))
+}
@@ -0,0 +1,87 @@
+use super::{blob::get_store, get_pool, DBObject, ToFallible};
+use crate::custom_error::{DataType, ErrorCode, Fallible};
+
+// 解碼後的上限
+const MAX_IMAGE_SIZE: usize = 5 * 1024 * 1024;
+
+pub struct Image {
+    pub blob_key: String,
+    pub content_type: String,
+}
+impl DBObject for Image {
+    const TYPE: DataType = DataType::Image;
+}
+
+/// 依檔頭判斷圖片格式，不支援的格式回傳 None
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// 檢查並解碼以 base64 編碼的圖片，回傳 (原始資料, 格式)
+pub fn decode_image(image: &str) -> Fallible<(Vec<u8>, &'static str)> {
+    let data = base64::decode(image)
+        .map_err(|e| ErrorCode::InvalidImage("base64 解碼失敗".to_owned()).context(e))?;
+    if data.len() > MAX_IMAGE_SIZE {
+        return Err(ErrorCode::InvalidImage(format!(
+            "圖片大小不可超過 {} MB",
+            MAX_IMAGE_SIZE / 1024 / 1024
+        ))
+        .into());
+    }
+    match sniff_content_type(&data) {
+        Some(content_type) => Ok((data, content_type)),
+        None => Err(ErrorCode::InvalidImage("僅支援 PNG、JPEG、GIF、WebP".to_owned()).into()),
+    }
+}
+
+pub async fn upload(owner_id: i64, image: String) -> Fallible<i64> {
+    let pool = get_pool();
+    let (data, content_type) = decode_image(&image)?;
+    let blob_key = get_store().put(&data).await?;
+    let id = sqlx::query!(
+        "
+        INSERT INTO images (blob_key, content_type, owner_id)
+        VALUES ($1, $2, $3)
+        RETURNING id
+        ",
+        blob_key,
+        content_type,
+        owner_id
+    )
+    .fetch_one(pool)
+    .await?
+    .id;
+    Ok(id)
+}
+
+pub async fn get(id: i64) -> Fallible<Image> {
+    let pool = get_pool();
+    let image = sqlx::query_as!(
+        Image,
+        "SELECT blob_key, content_type FROM images WHERE id = $1",
+        id
+    )
+    .fetch_one(pool)
+    .await
+    .to_fallible(id)?;
+    Ok(image)
+}
+
+/// 圖片不存在時回傳 None ，大頭貼的上傳者爲 Some(None)
+pub async fn get_owner(id: i64) -> Fallible<Option<Option<i64>>> {
+    let pool = get_pool();
+    let image = sqlx::query!("SELECT owner_id FROM images WHERE id = $1", id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(image.map(|image| image.owner_id))
+}
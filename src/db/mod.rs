@@ -8,10 +8,16 @@ pub mod article;
 pub mod article_content;
 pub mod article_statistics;
 pub mod avatar;
+pub mod blob;
 pub mod board;
+pub mod election;
 pub mod favorite;
+pub mod image;
+pub mod moderation;
 pub mod notification;
 pub mod party;
+pub mod poll;
+pub mod report;
 pub mod signup_invitations;
 pub mod subscribed_boards;
 pub mod user;
@@ -23,6 +29,7 @@ pub async fn init() -> Fallible<()> {
     let conf = &get_config().database;
     let pool = PgPool::connect(&conf.get_url()).await?;
     assert!(POOL.set(pool), "資料庫連接池被重複創建",);
+    blob::init();
     Ok(())
 }
 
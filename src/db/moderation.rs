@@ -0,0 +1,413 @@
+use super::get_pool;
+use crate::api::model::{ModerationAction, ModerationLog};
+use crate::custom_error::{DataType, ErrorCode, Fallible};
+use sqlx::PgConnection;
+use std::str::FromStr;
+
+/// 每個看板的置頂文章上限
+pub const MAX_PINNED: usize = 3;
+/// 有期限禁言的天數上限，更長者應改用永久禁言
+pub const MAX_BAN_DAYS: i64 = 3650;
+
+/// 看板執政黨的成員才能管理看板，個人看板則由擁有者管理
+pub async fn is_moderator(board_id: i64, user_id: i64) -> Fallible<bool> {
+    let pool = get_pool();
+    let record = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM party_members
+            INNER JOIN boards ON boards.ruling_party_id = party_members.party_id
+            WHERE boards.id = $1 AND party_members.user_id = $2 AND party_members.position > 0
+        ) OR EXISTS (
+            SELECT 1 FROM boards WHERE boards.id = $1 AND boards.owner_id = $2
+        ) AS "exists!"
+        "#,
+        board_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(record.exists)
+}
+
+pub async fn check_moderator(board_id: i64, user_id: i64) -> Fallible {
+    if is_moderator(board_id, user_id).await? {
+        Ok(())
+    } else {
+        Err(ErrorCode::PermissionDenied.context("僅執政黨成員可管理看板"))
+    }
+}
+
+/// 看板成員指該看板上任一政黨的成員
+pub async fn is_board_member(board_id: i64, user_id: i64) -> Fallible<bool> {
+    let pool = get_pool();
+    let record = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM party_members
+            INNER JOIN parties ON parties.id = party_members.party_id
+            WHERE parties.board_id = $1 AND party_members.user_id = $2
+                AND party_members.position > 0
+        ) AS "exists!"
+        "#,
+        board_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(record.exists)
+}
+
+/// 看板成員或執政黨成員才能查看看板的管理紀錄
+pub async fn check_board_member(board_id: i64, user_id: i64) -> Fallible {
+    if is_board_member(board_id, user_id).await? || is_moderator(board_id, user_id).await? {
+        Ok(())
+    } else {
+        Err(ErrorCode::PermissionDenied.context("僅看板成員可查看"))
+    }
+}
+
+pub async fn is_banned(board_id: i64, user_id: i64) -> Fallible<bool> {
+    let pool = get_pool();
+    let record = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM board_bans
+            WHERE board_id = $1 AND user_id = $2 AND (until IS NULL OR until > NOW())
+        ) AS "exists!"
+        "#,
+        board_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(record.exists)
+}
+
+async fn log(
+    conn: &mut PgConnection,
+    board_id: i64,
+    moderator_id: i64,
+    action: ModerationAction,
+    article_id: Option<i64>,
+    user_id: Option<i64>,
+    reason: &str,
+) -> Fallible {
+    sqlx::query!(
+        "
+        INSERT INTO moderation_logs (board_id, moderator_id, action, article_id, user_id, reason)
+        VALUES ($1, $2, $3::text::moderation_action, $4, $5, $6)
+        ",
+        board_id,
+        moderator_id,
+        action.to_string(),
+        article_id,
+        user_id,
+        reason
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+// 隱藏的文章查不到元資料，故直接查表
+async fn get_article_board(article_id: i64) -> Fallible<i64> {
+    let pool = get_pool();
+    let article = sqlx::query!("SELECT board_id FROM articles WHERE id = $1", article_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ErrorCode::NotFound(DataType::Article, article_id.to_string()).to_err())?;
+    Ok(article.board_id)
+}
+
+pub async fn set_hidden(
+    moderator_id: i64,
+    article_id: i64,
+    hidden: bool,
+    reason: &str,
+) -> Fallible {
+    let board_id = get_article_board(article_id).await?;
+    check_moderator(board_id, moderator_id).await?;
+    let mut conn = get_pool().begin().await?;
+    set_hidden_in(
+        &mut conn,
+        board_id,
+        moderator_id,
+        article_id,
+        hidden,
+        reason,
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// 在呼叫端的交易中隱藏文章並記錄，權限由呼叫端檢查
+pub async fn set_hidden_in(
+    conn: &mut PgConnection,
+    board_id: i64,
+    moderator_id: i64,
+    article_id: i64,
+    hidden: bool,
+    reason: &str,
+) -> Fallible {
+    sqlx::query!(
+        "UPDATE articles SET hidden = $1 WHERE id = $2",
+        hidden,
+        article_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    let action = if hidden {
+        ModerationAction::HideArticle
+    } else {
+        ModerationAction::UnhideArticle
+    };
+    log(
+        conn,
+        board_id,
+        moderator_id,
+        action,
+        Some(article_id),
+        None,
+        reason,
+    )
+    .await
+}
+
+pub async fn set_locked(
+    moderator_id: i64,
+    article_id: i64,
+    locked: bool,
+    reason: &str,
+) -> Fallible {
+    let board_id = get_article_board(article_id).await?;
+    check_moderator(board_id, moderator_id).await?;
+    let mut conn = get_pool().begin().await?;
+    sqlx::query!(
+        "UPDATE articles SET locked = $1 WHERE id = $2",
+        locked,
+        article_id
+    )
+    .execute(&mut conn)
+    .await?;
+    let action = if locked {
+        ModerationAction::LockArticle
+    } else {
+        ModerationAction::UnlockArticle
+    };
+    log(
+        &mut conn,
+        board_id,
+        moderator_id,
+        action,
+        Some(article_id),
+        None,
+        reason,
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub async fn set_pinned(moderator_id: i64, article_id: i64, pinned: bool) -> Fallible {
+    let board_id = get_article_board(article_id).await?;
+    check_moderator(board_id, moderator_id).await?;
+    let mut conn = get_pool().begin().await?;
+    if pinned {
+        // 鎖住看板，同時置頂的版主依序計數，才不會超過上限
+        sqlx::query!("SELECT id FROM boards WHERE id = $1 FOR UPDATE", board_id)
+            .fetch_one(&mut conn)
+            .await?;
+        let count = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "count!" FROM articles
+            WHERE board_id = $1 AND pinned AND id != $2
+            "#,
+            board_id,
+            article_id
+        )
+        .fetch_one(&mut conn)
+        .await?
+        .count;
+        if count as usize >= MAX_PINNED {
+            return Err(ErrorCode::PinLimitExceeded(MAX_PINNED).into());
+        }
+    }
+    sqlx::query!(
+        "
+        UPDATE articles SET pinned = $1, pin_time = CASE WHEN $1 THEN NOW() ELSE NULL END
+        WHERE id = $2
+        ",
+        pinned,
+        article_id
+    )
+    .execute(&mut conn)
+    .await?;
+    let action = if pinned {
+        ModerationAction::PinArticle
+    } else {
+        ModerationAction::UnpinArticle
+    };
+    log(
+        &mut conn,
+        board_id,
+        moderator_id,
+        action,
+        Some(article_id),
+        None,
+        "",
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub async fn set_featured(moderator_id: i64, article_id: i64, featured: bool) -> Fallible {
+    let board_id = get_article_board(article_id).await?;
+    check_moderator(board_id, moderator_id).await?;
+    let mut conn = get_pool().begin().await?;
+    sqlx::query!(
+        "UPDATE articles SET featured = $1 WHERE id = $2",
+        featured,
+        article_id
+    )
+    .execute(&mut conn)
+    .await?;
+    let action = if featured {
+        ModerationAction::FeatureArticle
+    } else {
+        ModerationAction::UnfeatureArticle
+    };
+    log(
+        &mut conn,
+        board_id,
+        moderator_id,
+        action,
+        Some(article_id),
+        None,
+        "",
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// `days` 爲 None 時永久禁言，重複禁言會覆蓋期限
+pub async fn ban(
+    moderator_id: i64,
+    board_id: i64,
+    user_id: i64,
+    days: Option<i64>,
+    reason: &str,
+) -> Fallible {
+    check_moderator(board_id, moderator_id).await?;
+    let mut conn = get_pool().begin().await?;
+    ban_in(&mut conn, board_id, moderator_id, user_id, days, reason).await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+/// 在呼叫端的交易中禁言並記錄，權限由呼叫端檢查
+pub async fn ban_in(
+    conn: &mut PgConnection,
+    board_id: i64,
+    moderator_id: i64,
+    user_id: i64,
+    days: Option<i64>,
+    reason: &str,
+) -> Fallible {
+    let until = match days {
+        Some(days) if !(1..=MAX_BAN_DAYS).contains(&days) => {
+            return Err(
+                ErrorCode::OutOfRange(format!("禁言天數需介於 1 到 {} 之間", MAX_BAN_DAYS)).into(),
+            );
+        }
+        Some(days) => Some(chrono::Utc::now() + chrono::Duration::days(days)),
+        None => None,
+    };
+    sqlx::query!(
+        "
+        INSERT INTO board_bans (board_id, user_id, until)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (board_id, user_id) DO UPDATE SET until = $3, create_time = NOW()
+        ",
+        board_id,
+        user_id,
+        until
+    )
+    .execute(&mut *conn)
+    .await?;
+    log(
+        conn,
+        board_id,
+        moderator_id,
+        ModerationAction::BanUser,
+        None,
+        Some(user_id),
+        reason,
+    )
+    .await
+}
+
+pub async fn unban(moderator_id: i64, board_id: i64, user_id: i64, reason: &str) -> Fallible {
+    check_moderator(board_id, moderator_id).await?;
+    let mut conn = get_pool().begin().await?;
+    sqlx::query!(
+        "DELETE FROM board_bans WHERE board_id = $1 AND user_id = $2",
+        board_id,
+        user_id
+    )
+    .execute(&mut conn)
+    .await?;
+    log(
+        &mut conn,
+        board_id,
+        moderator_id,
+        ModerationAction::UnbanUser,
+        None,
+        Some(user_id),
+        reason,
+    )
+    .await?;
+    conn.commit().await?;
+    Ok(())
+}
+
+pub async fn get_log(board_id: i64) -> Fallible<Vec<ModerationLog>> {
+    let pool = get_pool();
+    // XXX: 一旦 sqlx 自訂型別進化就改掉這段
+    let logs = sqlx::query!(
+        r#"
+        SELECT l.id, l.board_id, l.moderator_id, moderators.user_name AS moderator_name,
+            l.action::text AS "action!", l.article_id, articles.title AS "article_title?",
+            l.user_id, users.user_name AS "user_name?", l.reason, l.create_time
+        FROM moderation_logs l
+        INNER JOIN users moderators ON moderators.id = l.moderator_id
+        LEFT JOIN articles ON articles.id = l.article_id
+        LEFT JOIN users ON users.id = l.user_id
+        WHERE l.board_id = $1
+        ORDER BY l.create_time DESC
+        "#,
+        board_id
+    )
+    .fetch_all(pool)
+    .await?;
+    logs.into_iter()
+        .map(|l| -> Fallible<ModerationLog> {
+            Ok(ModerationLog {
+                id: l.id,
+                board_id: l.board_id,
+                moderator_id: l.moderator_id,
+                moderator_name: l.moderator_name,
+                action: ModerationAction::from_str(&l.action)?,
+                article_id: l.article_id,
+                article_title: l.article_title,
+                user_id: l.user_id,
+                user_name: l.user_name,
+                reason: l.reason,
+                create_time: l.create_time,
+            })
+        })
+        .collect()
+}
@@ -1,6 +1,7 @@
-use super::{get_pool, DBObject, ToFallible};
-use crate::api::model::Party;
-use crate::custom_error::{DataType, Fallible};
+use super::{get_pool, DBObject, ToFallible, ToTypedFallible};
+use crate::api::model::{Party, PartyMember, PartyPosition};
+use crate::config::get_config;
+use crate::custom_error::{DataType, ErrorCode, Fallible};
 use sqlx::PgConnection;
 
 impl DBObject for Party {
@@ -34,7 +35,7 @@ pub async fn get_by_member_id(id: i64) -> Fallible<Vec<Party>> {
     let parties = parties!(
         "
     INNER JOIN party_members ON parties.id = party_members.party_id
-    WHERE user_id = $1
+    WHERE user_id = $1 AND position > 0
         ",
         id
     )
@@ -53,14 +54,15 @@ pub async fn create(
         Some(board_name) => {
             sqlx::query!(
                 "
-                INSERT INTO parties (party_name, board_id)
-                SELECT $1, boards.id
+                INSERT INTO parties (party_name, board_id, chairman_id)
+                SELECT $1, boards.id, $3
                 FROM boards
                 WHERE boards.board_name = $2
                 RETURNING id
                 ",
                 party_name,
-                board_name
+                board_name,
+                chairman_id
             )
             .fetch_one(pool)
             .await?
@@ -69,10 +71,11 @@ pub async fn create(
         None => {
             sqlx::query!(
                 "
-                INSERT INTO parties (party_name)
-                VALUES ($1) RETURNING id
+                INSERT INTO parties (party_name, chairman_id)
+                VALUES ($1, $2) RETURNING id
                 ",
                 party_name,
+                chairman_id
             )
             .fetch_one(pool)
             .await?
@@ -81,25 +84,30 @@ pub async fn create(
     };
     sqlx::query!(
         "
-        INSERT INTO party_members (party_id, user_id)
-        VALUES ($1, $2) RETURNING id
+        INSERT INTO party_members (party_id, user_id, position)
+        VALUES ($1, $2, $3) RETURNING id
         ",
         party_id,
         chairman_id,
+        PartyPosition::Chairman.to_level()
     )
     .fetch_one(pool)
     .await?;
     Ok(party_id)
 }
 
+/// 流亡政黨成爲看板的執政黨，與 `relocate` 同樣只接受流亡政黨
 pub async fn change_board(conn: &mut PgConnection, party_id: i64, board_id: i64) -> Fallible<()> {
-    sqlx::query!(
-        "UPDATE parties SET board_id = $1, ruling = true where id = $2",
+    let result = sqlx::query!(
+        "UPDATE parties SET board_id = $1, ruling = true where id = $2 AND board_id IS NULL",
         board_id,
         party_id
     )
     .execute(conn)
     .await?;
+    if result.rows_affected() == 0 {
+        return Err(ErrorCode::PermissionDenied.context("僅流亡政黨可創立看板"));
+    }
     Ok(())
 }
 
@@ -110,3 +118,375 @@ pub async fn get_by_board_id(id: i64) -> Fallible<Vec<Party>> {
         .await?;
     Ok(parties)
 }
+
+impl PartyPosition {
+    pub fn to_level(self) -> i16 {
+        self as i16
+    }
+    pub fn from_level(level: i16) -> Fallible<Self> {
+        match level {
+            0 => Ok(PartyPosition::Applicant),
+            1 => Ok(PartyPosition::Member),
+            2 => Ok(PartyPosition::Executive),
+            3 => Ok(PartyPosition::Chairman),
+            _ => Err(ErrorCode::Other(format!("不合法的政黨地位 {}", level)).into()),
+        }
+    }
+}
+
+pub async fn get_members(party_id: i64) -> Fallible<Vec<PartyMember>> {
+    let pool = get_pool();
+    let members = sqlx::query!(
+        "
+        SELECT party_members.user_id, users.user_name, party_members.position,
+            party_members.dedication_ratio, party_members.create_time
+        FROM party_members
+        INNER JOIN users ON users.id = party_members.user_id
+        WHERE party_members.party_id = $1
+        ORDER BY party_members.position DESC, party_members.create_time
+        ",
+        party_id
+    )
+    .fetch_all(pool)
+    .await?;
+    members
+        .into_iter()
+        .map(|m| -> Fallible<PartyMember> {
+            Ok(PartyMember {
+                user_id: m.user_id,
+                user_name: m.user_name,
+                position: PartyPosition::from_level(m.position)?,
+                dedication_ratio: m.dedication_ratio,
+                create_time: m.create_time,
+            })
+        })
+        .collect()
+}
+
+/// 非成員回傳 None，申請中的人回傳 Applicant
+pub async fn get_position(party_id: i64, user_id: i64) -> Fallible<Option<PartyPosition>> {
+    let pool = get_pool();
+    let member = sqlx::query!(
+        "SELECT position FROM party_members WHERE party_id = $1 AND user_id = $2",
+        party_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    member
+        .map(|m| PartyPosition::from_level(m.position))
+        .transpose()
+}
+
+async fn check_position(
+    party_id: i64,
+    user_id: i64,
+    required: PartyPosition,
+) -> Fallible<PartyPosition> {
+    match get_position(party_id, user_id).await? {
+        Some(position) if position >= required => Ok(position),
+        _ => Err(ErrorCode::PermissionDenied.context("政黨地位不足")),
+    }
+}
+
+fn member_not_found(user_id: i64) -> crate::custom_error::Error {
+    ErrorCode::NotFound(DataType::PartyMember, user_id.to_string()).into()
+}
+
+pub async fn apply(party_id: i64, user_id: i64) -> Fallible {
+    let pool = get_pool();
+    sqlx::query!("SELECT id FROM parties WHERE id = $1", party_id)
+        .fetch_one(pool)
+        .await
+        .to_typed_fallible(DataType::Party, party_id)?;
+    let result = sqlx::query!(
+        "
+        INSERT INTO party_members (party_id, user_id, position)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (party_id, user_id) DO NOTHING
+        ",
+        party_id,
+        user_id,
+        PartyPosition::Applicant.to_level()
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ErrorCode::AlreadyInParty.into());
+    }
+    Ok(())
+}
+
+/// 幹部以上可審核申請，拒絕即刪除申請
+pub async fn review_application(
+    operator_id: i64,
+    party_id: i64,
+    user_id: i64,
+    accept: bool,
+) -> Fallible {
+    check_position(party_id, operator_id, PartyPosition::Executive).await?;
+    let pool = get_pool();
+    let result = if accept {
+        sqlx::query!(
+            "
+            UPDATE party_members SET position = $1, create_time = NOW()
+            WHERE party_id = $2 AND user_id = $3 AND position = $4
+            ",
+            PartyPosition::Member.to_level(),
+            party_id,
+            user_id,
+            PartyPosition::Applicant.to_level()
+        )
+        .execute(pool)
+        .await?
+    } else {
+        sqlx::query!(
+            "DELETE FROM party_members WHERE party_id = $1 AND user_id = $2 AND position = $3",
+            party_id,
+            user_id,
+            PartyPosition::Applicant.to_level()
+        )
+        .execute(pool)
+        .await?
+    };
+    if result.rows_affected() == 0 {
+        return Err(member_not_found(user_id));
+    }
+    Ok(())
+}
+
+/// 退黨或撤回申請，黨主席須先交棒
+pub async fn leave(party_id: i64, user_id: i64) -> Fallible {
+    let pool = get_pool();
+    match get_position(party_id, user_id).await? {
+        None => return Err(member_not_found(user_id)),
+        Some(PartyPosition::Chairman) => {
+            return Err(ErrorCode::PermissionDenied.context("黨主席須先交棒才能退黨"))
+        }
+        _ => (),
+    }
+    sqlx::query!(
+        "DELETE FROM party_members WHERE party_id = $1 AND user_id = $2",
+        party_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 幹部以上可開除地位比自己低的黨員
+pub async fn expel(operator_id: i64, party_id: i64, user_id: i64) -> Fallible {
+    let operator = check_position(party_id, operator_id, PartyPosition::Executive).await?;
+    match get_position(party_id, user_id).await? {
+        None | Some(PartyPosition::Applicant) => return Err(member_not_found(user_id)),
+        Some(position) if position >= operator => {
+            return Err(ErrorCode::PermissionDenied.context("只能開除地位比自己低的黨員"))
+        }
+        _ => (),
+    }
+    let pool = get_pool();
+    sqlx::query!(
+        "DELETE FROM party_members WHERE party_id = $1 AND user_id = $2",
+        party_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 僅黨主席可任免幹部，指定他人爲黨主席即交棒，原黨主席降爲幹部
+pub async fn set_position(
+    operator_id: i64,
+    party_id: i64,
+    user_id: i64,
+    position: PartyPosition,
+) -> Fallible {
+    check_position(party_id, operator_id, PartyPosition::Chairman).await?;
+    if operator_id == user_id {
+        return Err(ErrorCode::PermissionDenied.context("不能變更自己的地位"));
+    }
+    match get_position(party_id, user_id).await? {
+        None | Some(PartyPosition::Applicant) => return Err(member_not_found(user_id)),
+        _ => (),
+    }
+    if position == PartyPosition::Applicant {
+        return Err(ErrorCode::PermissionDenied.context("不能將黨員改回申請中"));
+    }
+    let mut conn = get_pool().begin().await?;
+    sqlx::query!(
+        "UPDATE party_members SET position = $1 WHERE party_id = $2 AND user_id = $3",
+        position.to_level(),
+        party_id,
+        user_id
+    )
+    .execute(&mut conn)
+    .await?;
+    if position == PartyPosition::Chairman {
+        sqlx::query!(
+            "UPDATE party_members SET position = $1 WHERE party_id = $2 AND user_id = $3",
+            PartyPosition::Executive.to_level(),
+            party_id,
+            operator_id
+        )
+        .execute(&mut conn)
+        .await?;
+        sqlx::query!(
+            "UPDATE parties SET chairman_id = $1 WHERE id = $2",
+            user_id,
+            party_id
+        )
+        .execute(&mut conn)
+        .await?;
+    }
+    conn.commit().await?;
+    Ok(())
+}
+
+/// 奉獻比率的範圍，同資料庫的限制
+pub const DEDICATION_RATIO_RANGE: std::ops::RangeInclusive<i16> = 10..=100;
+
+pub async fn set_dedication_ratio(party_id: i64, user_id: i64, ratio: i16) -> Fallible {
+    if !DEDICATION_RATIO_RANGE.contains(&ratio) {
+        return Err(ErrorCode::OutOfRange(format!(
+            "奉獻比率需介於 {} 到 {}",
+            DEDICATION_RATIO_RANGE.start(),
+            DEDICATION_RATIO_RANGE.end()
+        ))
+        .into());
+    }
+    let pool = get_pool();
+    let result = sqlx::query!(
+        "
+        UPDATE party_members SET dedication_ratio = $1
+        WHERE party_id = $2 AND user_id = $3 AND position > 0
+        ",
+        ratio,
+        party_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(member_not_found(user_id));
+    }
+    Ok(())
+}
+
+/// 文章獲得的鍵能依作者在該看板各政黨的奉獻比率轉入政黨，並留下紀錄
+pub(super) async fn transfer_energy(
+    conn: &mut PgConnection,
+    article_id: i64,
+    energy: i16,
+) -> Fallible {
+    sqlx::query!(
+        "
+        WITH transfers AS (
+            INSERT INTO party_energy_transfers (party_id, user_id, article_id, energy)
+            SELECT party_members.party_id, party_members.user_id, articles.id,
+                $2::int * party_members.dedication_ratio / 100
+            FROM articles
+            INNER JOIN parties ON parties.board_id = articles.board_id
+            INNER JOIN party_members
+                ON party_members.party_id = parties.id AND party_members.user_id = articles.author_id
+            WHERE articles.id = $1 AND party_members.position > 0
+                AND $2::int * party_members.dedication_ratio / 100 != 0
+            RETURNING party_id, energy
+        )
+        UPDATE parties SET energy = parties.energy + transfers.energy
+        FROM transfers WHERE parties.id = transfers.party_id
+        ",
+        article_id,
+        energy as i32
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 看板上的政黨依鍵能由高到低排列
+pub async fn get_energy_ranking(board_id: i64) -> Fallible<Vec<Party>> {
+    let pool = get_pool();
+    let parties = parties!(
+        "WHERE parties.board_id = $1 ORDER BY parties.energy DESC, parties.id",
+        board_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(parties)
+}
+
+pub async fn is_exiled(party_id: i64) -> Fallible<bool> {
+    let pool = get_pool();
+    let party = sqlx::query!("SELECT board_id FROM parties WHERE id = $1", party_id)
+        .fetch_one(pool)
+        .await
+        .to_typed_fallible(DataType::Party, party_id)?;
+    Ok(party.board_id.is_none())
+}
+
+/// 黨主席才能代表政黨創板或遷移，且政黨需達設定的黨員數與鍵能
+pub async fn check_eligibility(
+    operator_id: i64,
+    party_id: i64,
+    min_members: i64,
+    min_energy: i32,
+) -> Fallible {
+    check_position(party_id, operator_id, PartyPosition::Chairman).await?;
+    let pool = get_pool();
+    let party = sqlx::query!(
+        r#"
+        SELECT parties.energy, (
+            SELECT COUNT(*) FROM party_members
+            WHERE party_members.party_id = parties.id AND party_members.position > 0
+        ) AS "member_count!"
+        FROM parties WHERE parties.id = $1
+        "#,
+        party_id
+    )
+    .fetch_one(pool)
+    .await
+    .to_typed_fallible(DataType::Party, party_id)?;
+    if party.member_count < min_members {
+        return Err(ErrorCode::InsufficientPartyMembers(min_members).into());
+    }
+    if party.energy < min_energy {
+        return Err(ErrorCode::InsufficientPartyEnergy(min_energy).into());
+    }
+    Ok(())
+}
+
+/// 流亡政黨遷入既有看板成爲在野黨，已在看板上的政黨不能遷出
+pub async fn relocate(operator_id: i64, party_id: i64, board_name: &str) -> Fallible {
+    let config = &get_config().party;
+    check_eligibility(
+        operator_id,
+        party_id,
+        config.relocation_members,
+        config.relocation_energy,
+    )
+    .await?;
+    let pool = get_pool();
+    let board = sqlx::query!(
+        "SELECT id FROM boards WHERE board_name = $1 AND board_type = 'general'",
+        board_name
+    )
+    .fetch_one(pool)
+    .await
+    .to_typed_fallible(DataType::Board, board_name)?;
+    let result = sqlx::query!(
+        "
+        UPDATE parties SET board_id = $1
+        WHERE id = $2 AND board_id IS NULL
+        ",
+        board.id,
+        party_id
+    )
+    .execute(pool)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(ErrorCode::PermissionDenied.context("僅流亡政黨可遷入看板"));
+    }
+    Ok(())
+}
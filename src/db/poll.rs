@@ -0,0 +1,140 @@
+use super::get_pool;
+use crate::api::model::{PollTally, PollVote};
+use crate::custom_error::{DataType, ErrorCode, Fallible};
+use chrono::{Duration, Utc};
+use force::{parse_category, BasicDataType, Poll};
+use std::collections::HashMap;
+
+// 各文章的票數，只列出有人投的選項
+pub async fn get_tallies(article_ids: &[i64]) -> Fallible<HashMap<i64, Vec<PollTally>>> {
+    let pool = get_pool();
+    let rows = sqlx::query!(
+        r#"
+        SELECT article_id, field_name, choice, COUNT(*) AS "count!" FROM poll_votes
+        WHERE article_id = ANY($1)
+        GROUP BY article_id, field_name, choice
+        "#,
+        article_ids
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut tallies: HashMap<i64, Vec<PollTally>> = HashMap::new();
+    for r in rows.into_iter() {
+        tallies.entry(r.article_id).or_default().push(PollTally {
+            field_name: r.field_name,
+            choice: r.choice,
+            count: r.count,
+        });
+    }
+    Ok(tallies)
+}
+
+// 使用者在各文章投下的票
+pub async fn get_votes(article_ids: &[i64], user_id: i64) -> Fallible<HashMap<i64, Vec<PollVote>>> {
+    let pool = get_pool();
+    let rows = sqlx::query!(
+        "
+        SELECT article_id, field_name, choice FROM poll_votes
+        WHERE user_id = $1 AND article_id = ANY($2)
+        ORDER BY id
+        ",
+        user_id,
+        article_ids
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut votes: HashMap<i64, Vec<PollVote>> = HashMap::new();
+    for r in rows.into_iter() {
+        let article_votes = votes.entry(r.article_id).or_default();
+        match article_votes
+            .iter_mut()
+            .find(|v| v.field_name == r.field_name)
+        {
+            Some(vote) => vote.choices.push(r.choice),
+            None => article_votes.push(PollVote {
+                field_name: r.field_name,
+                choices: vec![r.choice],
+            }),
+        }
+    }
+    Ok(votes)
+}
+
+fn check_choices(poll: &Poll, choices: &[String]) -> Fallible {
+    if !poll.multiple && choices.len() > 1 {
+        return Err(ErrorCode::InvalidVote("單選投票只能選一項".to_owned()).into());
+    }
+    for (i, choice) in choices.iter().enumerate() {
+        if !poll.options.contains(choice) {
+            return Err(ErrorCode::InvalidVote(format!("不存在選項 {}", choice)).into());
+        }
+        if choices[..i].contains(choice) {
+            return Err(ErrorCode::InvalidVote(format!("重複選擇 {}", choice)).into());
+        }
+    }
+    Ok(())
+}
+
+/// 投票，會取代使用者先前在該欄位投下的票，`choices` 爲空則視同撤票
+pub async fn cast_vote(
+    user_id: i64,
+    article_id: i64,
+    field_name: &str,
+    choices: Vec<String>,
+) -> Fallible<Vec<PollTally>> {
+    let meta = super::article::get_meta_by_id(article_id).await?;
+    let category = parse_category(&meta.category_source)?;
+    let poll = category
+        .fields
+        .iter()
+        .find_map(|f| match f.datatype.basic_type() {
+            BasicDataType::Poll(poll) if f.name == field_name => Some(poll),
+            _ => None,
+        })
+        .ok_or(ErrorCode::NotFound(DataType::PollField, field_name.to_owned()).to_err())?;
+    if let Some(hours) = poll.close_hours {
+        if Utc::now() > meta.create_time + Duration::hours(hours as i64) {
+            return Err(ErrorCode::PollClosed.into());
+        }
+    }
+    check_choices(poll, &choices)?;
+
+    let mut conn = get_pool().begin().await?;
+    // 唯一鍵含選項，擋不住同一人同時送出不同選項，故以交易鎖讓同一人的投票依序進行
+    sqlx::query!(
+        r#"
+        SELECT 1 AS "locked!" FROM pg_advisory_xact_lock(
+            hashtext($1::bigint::text || ':' || $2::text || ':' || $3::bigint::text)
+        )
+        "#,
+        article_id,
+        field_name,
+        user_id
+    )
+    .fetch_one(&mut conn)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM poll_votes WHERE article_id = $1 AND field_name = $2 AND user_id = $3",
+        article_id,
+        field_name,
+        user_id
+    )
+    .execute(&mut conn)
+    .await?;
+    sqlx::query!(
+        "
+        INSERT INTO poll_votes (article_id, field_name, user_id, choice)
+        SELECT $1, $2, $3, UNNEST($4::text[])
+        ",
+        article_id,
+        field_name,
+        user_id,
+        &choices
+    )
+    .execute(&mut conn)
+    .await?;
+    conn.commit().await?;
+
+    let mut tallies = get_tallies(&[article_id]).await?;
+    Ok(tallies.remove(&article_id).unwrap_or_default())
+}
@@ -0,0 +1,133 @@
+use super::get_pool;
+use crate::api::model::{Report, ReportReason, ReportResolution};
+use crate::custom_error::{DataType, ErrorCode, Fallible};
+use sqlx::PgConnection;
+use std::str::FromStr;
+
+/// 同一人對同一篇文章只有一筆檢舉，未處理前再次檢舉會覆蓋理由
+pub async fn create(
+    reporter_id: i64,
+    article_id: i64,
+    reason: ReportReason,
+    detail: &str,
+) -> Fallible {
+    let pool = get_pool();
+    // 確認文章存在且未被隱藏
+    super::article::get_meta_by_id(article_id).await?;
+    sqlx::query!(
+        "
+        INSERT INTO reports (article_id, reporter_id, reason, detail)
+        VALUES ($1, $2, $3::text::report_reason, $4)
+        ON CONFLICT (article_id, reporter_id) DO UPDATE
+        SET reason = $3::text::report_reason, detail = $4, create_time = NOW()
+        WHERE reports.resolution IS NULL
+        ",
+        article_id,
+        reporter_id,
+        reason.to_string(),
+        detail
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 看板上尚未處理的檢舉，舊的在前
+pub async fn get_queue(board_id: i64) -> Fallible<Vec<Report>> {
+    let pool = get_pool();
+    // XXX: 一旦 sqlx 自訂型別進化就改掉這段
+    let reports = sqlx::query!(
+        r#"
+        SELECT r.id, r.article_id, articles.title AS article_title,
+            articles.author_id, authors.user_name AS author_name,
+            r.reporter_id, reporters.user_name AS reporter_name,
+            r.reason::text AS "reason!", r.detail, r.create_time
+        FROM reports r
+        INNER JOIN articles ON articles.id = r.article_id
+        INNER JOIN users authors ON authors.id = articles.author_id
+        INNER JOIN users reporters ON reporters.id = r.reporter_id
+        WHERE articles.board_id = $1 AND r.resolution IS NULL
+        ORDER BY r.create_time
+        "#,
+        board_id
+    )
+    .fetch_all(pool)
+    .await?;
+    reports
+        .into_iter()
+        .map(|r| -> Fallible<Report> {
+            Ok(Report {
+                id: r.id,
+                article_id: r.article_id,
+                article_title: r.article_title,
+                author_id: r.author_id,
+                author_name: r.author_name,
+                reporter_id: r.reporter_id,
+                reporter_name: r.reporter_name,
+                reason: ReportReason::from_str(&r.reason)?,
+                detail: r.detail,
+                create_time: r.create_time,
+            })
+        })
+        .collect()
+}
+
+pub struct ReportTarget {
+    pub article_id: i64,
+    pub board_id: i64,
+    pub author_id: i64,
+}
+
+/// 找出未處理的檢舉所指向的文章，並鎖住該文章直到交易結束
+///
+/// 同一篇文章的檢舉同時只能有一人處理，後到者等前者結案後會找不到檢舉
+pub async fn lock_target(conn: &mut PgConnection, report_id: i64) -> Fallible<ReportTarget> {
+    // 先鎖文章再查檢舉，等到鎖後的新查詢才看得到前一人的結案
+    sqlx::query!(
+        "
+        SELECT id FROM articles
+        WHERE id = (SELECT article_id FROM reports WHERE id = $1)
+        FOR UPDATE
+        ",
+        report_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    let target = sqlx::query_as!(
+        ReportTarget,
+        "
+        SELECT r.article_id, articles.board_id, articles.author_id
+        FROM reports r
+        INNER JOIN articles ON articles.id = r.article_id
+        WHERE r.id = $1 AND r.resolution IS NULL
+        ",
+        report_id
+    )
+    .fetch_optional(conn)
+    .await?
+    .ok_or(ErrorCode::NotFound(DataType::Report, report_id.to_string()).to_err())?;
+    Ok(target)
+}
+
+/// 一併結案同一篇文章所有未處理的檢舉，回傳檢舉人
+pub async fn resolve(
+    conn: &mut PgConnection,
+    resolver_id: i64,
+    article_id: i64,
+    resolution: ReportResolution,
+) -> Fallible<Vec<i64>> {
+    let reporters = sqlx::query!(
+        "
+        UPDATE reports
+        SET resolution = $1::text::report_resolution, resolver_id = $2, resolve_time = NOW()
+        WHERE article_id = $3 AND resolution IS NULL
+        RETURNING reporter_id
+        ",
+        resolution.to_string(),
+        resolver_id,
+        article_id
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(reporters.into_iter().map(|r| r.reporter_id).collect())
+}
@@ -143,7 +143,7 @@ pub async fn signup(name: &str, password: &str, email: &str) -> Fallible<i64> {
     let salt = rand::thread_rng().gen::<[u8; 16]>();
     let hash = argon2::hash_raw(password.as_bytes(), &salt, &argon2::Config::default())?;
     log::trace!("生成使用者 {}:{} 的鹽及雜湊", name, email);
-    let pool = get_pool();
+    let mut conn = get_pool().begin().await?;
     let res = sqlx::query!(
         "INSERT INTO users (user_name, password_hashed, salt, email) VALUES ($1, $2, $3, $4) RETURNING id",
         name,
@@ -151,8 +151,10 @@ pub async fn signup(name: &str, password: &str, email: &str) -> Fallible<i64> {
         salt.to_vec(),
         email,
     )
-    .fetch_one(pool)
+    .fetch_one(&mut conn)
     .await?;
+    super::board::create_personal(&mut conn, res.id, name).await?;
+    conn.commit().await?;
     log::trace!("成功新增使用者 {}:{}", name, email);
     Ok(res.id)
 }
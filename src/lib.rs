@@ -30,8 +30,9 @@ mod product {
     use hyper::header;
     use hyper::header::HeaderValue;
     use hyper::{HeaderMap, Response};
-    use redis::AsyncCommands;
+    use std::collections::HashMap;
     use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
 
     #[async_trait]
     pub trait Context {
@@ -48,20 +49,59 @@ mod product {
     pub struct Ctx {
         pub headers: HeaderMap<HeaderValue>,
         pub resp: Response<String>,
+        /// 本次請求中設下或清除的 cookie ，同一批次的各請求共用
+        cookies: Arc<Mutex<HashMap<String, Cookie<'static>>>>,
     }
 
     // XXX: 明碼傳輸，先頂着用，上線前必須處理安全問題
     impl Ctx {
-        fn set_session<T: ToString>(&mut self, key: &str, value: T) -> Fallible<()> {
-            self.resp.headers_mut().insert(
-                header::SET_COOKIE,
-                HeaderValue::from_str(
-                    &Cookie::build(key, &value.to_string()).finish().to_string(),
-                )?,
-            );
+        pub fn new(headers: HeaderMap<HeaderValue>) -> Self {
+            Ctx {
+                headers,
+                resp: Response::new(String::new()),
+                cookies: Default::default(),
+            }
+        }
+        /// 產生一份共用 cookie 的 `Ctx` ，供批次請求並行執行
+        pub fn share(&self) -> Self {
+            Ctx {
+                headers: self.headers.clone(),
+                resp: Response::new(String::new()),
+                cookies: self.cookies.clone(),
+            }
+        }
+        /// 依目前的 cookie 重寫回應的 Set-Cookie ，每個 cookie 只留最後一次的設定
+        pub fn write_cookies(&self, headers: &mut HeaderMap<HeaderValue>) -> Fallible<()> {
+            headers.remove(header::SET_COOKIE);
+            for cookie in self.cookies.lock().unwrap().values() {
+                headers.append(
+                    header::SET_COOKIE,
+                    HeaderValue::from_str(&cookie.to_string())?,
+                );
+            }
             Ok(())
         }
+        fn put_cookie(&mut self, cookie: Cookie<'static>) -> Fallible<()> {
+            self.cookies
+                .lock()
+                .unwrap()
+                .insert(cookie.name().to_owned(), cookie);
+            let mut headers = std::mem::take(self.resp.headers_mut());
+            let res = self.write_cookies(&mut headers);
+            *self.resp.headers_mut() = headers;
+            res
+        }
+        fn set_session<T: ToString>(&mut self, key: &str, value: T) -> Fallible<()> {
+            self.put_cookie(Cookie::build(key.to_owned(), value.to_string()).finish())
+        }
         fn get_session<T: FromStr>(&mut self, key: &str) -> Option<T> {
+            // 先前設下或清除的 cookie 優先，批次請求才看得到其它請求的登入登出
+            if let Some(cookie) = self.cookies.lock().unwrap().get(key) {
+                return match cookie.value() {
+                    "" => None,
+                    v => v.parse::<T>().ok(),
+                };
+            }
             self.headers
                 .get(header::COOKIE)
                 .and_then(|v| v.to_str().ok())
@@ -79,16 +119,11 @@ mod product {
         }
         fn forget_session(&mut self, key: &str) -> Fallible<()> {
             use time::OffsetDateTime;
-            self.resp.headers_mut().insert(
-                header::SET_COOKIE,
-                HeaderValue::from_str(
-                    &Cookie::build(key, "")
-                        .expires(OffsetDateTime::now_utc())
-                        .finish()
-                        .to_string(),
-                )?,
-            );
-            Ok(())
+            self.put_cookie(
+                Cookie::build(key.to_owned(), "")
+                    .expires(OffsetDateTime::now_utc())
+                    .finish(),
+            )
         }
     }
     fn gen_token() -> String {
@@ -100,12 +135,8 @@ mod product {
     #[async_trait]
     impl Context for Ctx {
         async fn remember_id(&mut self, id: i64) -> Fallible<()> {
-            let mut conn = crate::redis::get_conn().await?;
             let token = gen_token();
-            // NOTE: 不知道第三個泛型參數什麼作用
-            conn.set::<&str, i64, ()>(&token, id).await?;
-            // TODO: 設置到 config.toml
-            conn.expire(&token, 60 * 60 * 24 * 7).await?;
+            crate::redis::session::create(&token, id).await?;
             self.set_session("token", token)
         }
 
@@ -115,17 +146,12 @@ mod product {
 
         async fn get_id(&mut self) -> Option<i64> {
             match self.get_session::<String>("token") {
-                Some(ref token) => match crate::redis::get_conn().await {
-                    Ok(mut conn) => match conn.get::<&str, i64>(token).await.ok() {
-                        Some(id) => {
-                            conn.expire::<&str, ()>(token, 60 * 60 * 24 * 7).await;
-                            Some(id)
-                        }
-                        None => {
-                            self.forget_session("token");
-                            None
-                        }
-                    },
+                Some(ref token) => match crate::redis::session::get(token).await {
+                    Ok(Some(id)) => Some(id),
+                    Ok(None) => {
+                        self.forget_session("token");
+                        None
+                    }
                     Err(_) => None,
                 },
                 None => None,
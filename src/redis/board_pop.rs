@@ -1,33 +1,73 @@
-use super::get_conn;
+use super::{get_store, parse_map, to_pairs};
 use crate::custom_error::Fallible;
-use redis::AsyncCommands;
 use std::collections::HashMap;
 
 const BOARD_KEY: &'static str = "boards";
+const BOARD_PRESENCE_PREFIX: &'static str = "board_presence";
+const USER_PRESENCE_PREFIX: &'static str = "user_presence";
+/// 在場紀錄的存活秒數，前端需在此之前送出心跳
+pub const PRESENCE_TTL: usize = 60;
 
+fn board_presence_key(board_id: i64, user_id: i64) -> String {
+    format!("{}:{}:{}", BOARD_PRESENCE_PREFIX, board_id, user_id)
+}
+fn user_presence_key(user_id: i64) -> String {
+    format!("{}:{}", USER_PRESENCE_PREFIX, user_id)
+}
+
+/// 記錄使用者正在看板上，逾時未續即自動失效。一人同時只算在一個看板
 pub async fn set_board_pop(user_id: i64, board_id: i64) -> Fallible {
     log::trace!("新增人氣記錄：user={}, board={}", user_id, board_id);
-    let mut conn = get_conn().await?;
-    if conn.exists(user_id).await? {
-        let old_board_id: i64 = conn.get(user_id).await?;
-        log::trace!("減去舊的人氣記錄：user={}, board={}", user_id, old_board_id);
-        conn.hincr(BOARD_KEY, old_board_id, -1).await?;
+    let store = get_store();
+    let user_key = user_presence_key(user_id);
+    let old_board_id: Option<i64> = store.get(&user_key).await?.and_then(|id| id.parse().ok());
+    if let Some(old_board_id) = old_board_id.filter(|id| *id != board_id) {
+        log::trace!("移除舊的人氣記錄：user={}, board={}", user_id, old_board_id);
+        store
+            .del(&board_presence_key(old_board_id, user_id))
+            .await?;
     }
-    conn.set(user_id, board_id).await?;
-    conn.hincr(BOARD_KEY, board_id, 1).await?;
+    store
+        .set(&user_key, &board_id.to_string(), Some(PRESENCE_TTL))
+        .await?;
+    store
+        .set(
+            &board_presence_key(board_id, user_id),
+            "1",
+            Some(PRESENCE_TTL),
+        )
+        .await?;
     Ok(())
 }
 
+/// 數出各看板目前在場的人數，並存起來供 `get_board_pop` 查詢
+pub async fn count_presence() -> Fallible<HashMap<i64, i64>> {
+    let store = get_store();
+    let keys = store.keys(&format!("{}:", BOARD_PRESENCE_PREFIX)).await?;
+    let mut counts = HashMap::<i64, i64>::new();
+    for key in keys.iter() {
+        let board_id = key.split(':').nth(1).and_then(|id| id.parse().ok());
+        if let Some(board_id) = board_id {
+            *counts.entry(board_id).or_default() += 1;
+        }
+    }
+    store.set_hash(BOARD_KEY, &to_pairs(&counts)).await?;
+    Ok(counts)
+}
+
 pub async fn get_board_pop(board_id: i64) -> Fallible<i64> {
     log::trace!("查詢 #{} 看板人氣", board_id);
-    let mut conn = get_conn().await?;
-    let pop: i64 = conn.hget(BOARD_KEY, board_id).await.unwrap_or(0);
+    let pop = get_store()
+        .get_hash_field(BOARD_KEY, &board_id.to_string())
+        .await
+        .unwrap_or_default()
+        .and_then(|pop| pop.parse().ok())
+        .unwrap_or(0);
     Ok(pop)
 }
 
 pub async fn get_all_board_pop() -> Fallible<HashMap<i64, i64>> {
     log::trace!("查詢全看板人氣");
-    let mut conn = get_conn().await?;
-    let map = conn.hgetall(BOARD_KEY).await.unwrap_or_default();
-    Ok(map)
+    let map = get_store().get_hash(BOARD_KEY).await.unwrap_or_default();
+    Ok(parse_map(map))
 }
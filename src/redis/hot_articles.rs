@@ -0,0 +1,36 @@
+use super::get_store;
+use crate::api::model::HotWindow;
+use crate::custom_error::Fallible;
+
+const KEY: &'static str = "hot_articles";
+
+fn key(window: HotWindow, board_id: Option<i64>) -> String {
+    match board_id {
+        Some(board_id) => format!("{}:{}:{}", KEY, window.to_string(), board_id),
+        None => format!("{}:{}", KEY, window.to_string()),
+    }
+}
+
+/// `board_id` 爲 None 時表全站。列表會自動過期，不再上榜的看板不必另外清除
+pub async fn set_hot_articles(
+    window: HotWindow,
+    board_id: Option<i64>,
+    articles: &[i64],
+    ttl: usize,
+) -> Fallible {
+    if articles.len() == 0 {
+        return Ok(());
+    }
+    let articles: Vec<_> = articles.iter().map(|id| id.to_string()).collect();
+    get_store()
+        .set_list(&key(window, board_id), &articles, Some(ttl))
+        .await
+}
+pub async fn get_hot_articles(window: HotWindow, board_id: Option<i64>) -> Fallible<Vec<i64>> {
+    log::trace!("查詢 {:?} 熱門文章，看板 {:?}", window, board_id);
+    let hot_articles = get_store().get_list(&key(window, board_id)).await?;
+    Ok(hot_articles
+        .iter()
+        .filter_map(|id| id.parse().ok())
+        .collect())
+}
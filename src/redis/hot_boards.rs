@@ -1,22 +1,52 @@
-use super::get_conn;
+use super::{get_store, parse_map, to_pairs};
+use crate::api::model::HotWindow;
 use crate::custom_error::Fallible;
-use redis::AsyncCommands;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
 
 const KEY: &'static str = "hot_boards";
+const VISITOR_KEY: &'static str = "board_visitors";
+/// 訪客雜湊表中記錄計算時間（毫秒時間戳）的欄位，與看板 id 不會衝突
+const TIME_FIELD: &'static str = "time";
 
-pub async fn set_hot_boards(boards: &[i64]) -> Fallible {
+fn key(window: HotWindow) -> String {
+    format!("{}:{}", KEY, window.to_string())
+}
+fn visitor_key(window: HotWindow) -> String {
+    format!("{}:{}", VISITOR_KEY, window.to_string())
+}
+
+pub async fn set_hot_boards(window: HotWindow, boards: &[i64]) -> Fallible {
     if boards.len() == 0 {
         return Ok(());
     }
-    log::trace!("設定熱門看板");
-    let mut conn = get_conn().await?;
-    conn.del(KEY).await?;
-    conn.rpush(KEY, boards).await?;
-    Ok(())
+    log::trace!("設定 {:?} 熱門看板", window);
+    let boards: Vec<_> = boards.iter().map(|id| id.to_string()).collect();
+    get_store().set_list(&key(window), &boards, None).await
+}
+pub async fn get_hot_boards(window: HotWindow) -> Fallible<Vec<i64>> {
+    log::trace!("查詢 {:?} 熱門看板", window);
+    let hot_boards = get_store().get_list(&key(window)).await?;
+    Ok(hot_boards.iter().filter_map(|id| id.parse().ok()).collect())
+}
+
+/// 各看板在場人數隨時間衰減的平均，及其計算時間
+pub async fn get_visitors(
+    window: HotWindow,
+) -> Fallible<(HashMap<i64, f64>, Option<DateTime<Utc>>)> {
+    let map = get_store().get_hash(&visitor_key(window)).await?;
+    let time = map
+        .get(TIME_FIELD)
+        .and_then(|t| t.parse().ok())
+        .map(|t| Utc.timestamp_millis(t));
+    Ok((parse_map(map), time))
 }
-pub async fn get_hot_boards() -> Fallible<Vec<i64>> {
-    log::trace!("查詢熱門看板");
-    let mut conn = get_conn().await?;
-    let hot_boards = conn.lrange(KEY, 0, -1).await?;
-    Ok(hot_boards)
+pub async fn set_visitors(
+    window: HotWindow,
+    visitors: &HashMap<i64, f64>,
+    time: DateTime<Utc>,
+) -> Fallible {
+    let mut pairs = to_pairs(visitors);
+    pairs.push((TIME_FIELD.to_owned(), time.timestamp_millis().to_string()));
+    get_store().set_hash(&visitor_key(window), &pairs).await
 }
@@ -0,0 +1,52 @@
+use super::get_store;
+use crate::custom_error::Fallible;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const LOCK_PREFIX: &'static str = "job_lock";
+const STATUS_PREFIX: &'static str = "job_status";
+
+fn lock_key(name: &str) -> String {
+    format!("{}:{}", LOCK_PREFIX, name)
+}
+fn status_key(name: &str) -> String {
+    format!("{}:{}", STATUS_PREFIX, name)
+}
+
+/// 背景工作最近一次執行的紀錄
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct JobStatus {
+    /// 最近一次執行工作的實例
+    pub instance: String,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_time: Option<DateTime<Utc>>,
+    /// 連續失敗的次數
+    pub failures: u32,
+}
+
+/// 取得或續約工作的鎖，鎖已在其它實例手上則回傳 false
+///
+/// 持有者每輪執行前續約，持有者停擺逾 `ttl` 秒後鎖自動釋出，由其它實例接手
+pub async fn acquire_lock(name: &str, instance: &str, ttl: usize) -> Fallible<bool> {
+    get_store()
+        .set_if_absent_or_eq(&lock_key(name), instance, ttl)
+        .await
+}
+
+pub async fn get_lock_holder(name: &str) -> Fallible<Option<String>> {
+    get_store().get(&lock_key(name)).await
+}
+
+pub async fn set_status(name: &str, status: &JobStatus) -> Fallible {
+    get_store()
+        .set(&status_key(name), &serde_json::to_string(status)?, None)
+        .await
+}
+pub async fn get_status(name: &str) -> Fallible<Option<JobStatus>> {
+    match get_store().get(&status_key(name)).await? {
+        Some(status) => Ok(Some(serde_json::from_str(&status)?)),
+        None => Ok(None),
+    }
+}
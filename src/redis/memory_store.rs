@@ -0,0 +1,266 @@
+use super::KvStore;
+use crate::custom_error::Fallible;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+enum Value {
+    Str(String),
+    Hash(HashMap<String, String>),
+    List(Vec<String>),
+}
+
+struct Entry {
+    value: Value,
+    expire_at: Option<Instant>,
+}
+
+impl Entry {
+    fn new(value: Value, ttl: Option<usize>) -> Self {
+        Entry {
+            value,
+            expire_at: ttl.map(|ttl| Instant::now() + Duration::from_secs(ttl as u64)),
+        }
+    }
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expire_at.map_or(false, |t| t <= now)
+    }
+}
+
+/// 存於行程記憶體，供未架設 redis 的單機部署與測試使用
+///
+/// 逾時的鍵在存取時才清除
+pub struct MemoryKvStore {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryKvStore {
+    pub fn new() -> Self {
+        MemoryKvStore {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+    /// 在鎖內操作，逾時的鍵先移除，使 `f` 看不到它
+    fn with_entries<T>(&self, key: &str, f: impl FnOnce(&mut HashMap<String, Entry>) -> T) -> T {
+        let mut entries = self.entries.lock().unwrap();
+        if entries
+            .get(key)
+            .map_or(false, |e| e.is_expired(Instant::now()))
+        {
+            entries.remove(key);
+        }
+        f(&mut entries)
+    }
+}
+
+#[async_trait]
+impl KvStore for MemoryKvStore {
+    async fn get(&self, key: &str) -> Fallible<Option<String>> {
+        Ok(self.with_entries(key, |entries| match entries.get(key) {
+            Some(Entry {
+                value: Value::Str(s),
+                ..
+            }) => Some(s.clone()),
+            _ => None,
+        }))
+    }
+    async fn set(&self, key: &str, value: &str, ttl: Option<usize>) -> Fallible {
+        self.with_entries(key, |entries| {
+            entries.insert(
+                key.to_owned(),
+                Entry::new(Value::Str(value.to_owned()), ttl),
+            );
+        });
+        Ok(())
+    }
+    async fn expire(&self, key: &str, ttl: usize) -> Fallible {
+        self.with_entries(key, |entries| {
+            if let Some(entry) = entries.get_mut(key) {
+                entry.expire_at = Some(Instant::now() + Duration::from_secs(ttl as u64));
+            }
+        });
+        Ok(())
+    }
+    async fn del(&self, key: &str) -> Fallible {
+        self.with_entries(key, |entries| entries.remove(key));
+        Ok(())
+    }
+    async fn set_if_absent_or_eq(&self, key: &str, value: &str, ttl: usize) -> Fallible<bool> {
+        Ok(self.with_entries(key, |entries| {
+            let writable = match entries.get(key) {
+                Some(Entry {
+                    value: Value::Str(s),
+                    ..
+                }) => s == value,
+                Some(_) => false,
+                None => true,
+            };
+            if writable {
+                entries.insert(
+                    key.to_owned(),
+                    Entry::new(Value::Str(value.to_owned()), Some(ttl)),
+                );
+            }
+            writable
+        }))
+    }
+    async fn keys(&self, prefix: &str) -> Fallible<Vec<String>> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, e| !e.is_expired(now));
+        Ok(entries
+            .keys()
+            .filter(|k| k.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+    async fn set_hash(&self, key: &str, fields: &[(String, String)]) -> Fallible {
+        self.with_entries(key, |entries| {
+            if fields.len() == 0 {
+                entries.remove(key);
+            } else {
+                let hash = fields.iter().cloned().collect();
+                entries.insert(key.to_owned(), Entry::new(Value::Hash(hash), None));
+            }
+        });
+        Ok(())
+    }
+    async fn get_hash(&self, key: &str) -> Fallible<HashMap<String, String>> {
+        Ok(self.with_entries(key, |entries| match entries.get(key) {
+            Some(Entry {
+                value: Value::Hash(hash),
+                ..
+            }) => hash.clone(),
+            _ => HashMap::new(),
+        }))
+    }
+    async fn get_hash_field(&self, key: &str, field: &str) -> Fallible<Option<String>> {
+        Ok(self.with_entries(key, |entries| match entries.get(key) {
+            Some(Entry {
+                value: Value::Hash(hash),
+                ..
+            }) => hash.get(field).cloned(),
+            _ => None,
+        }))
+    }
+    async fn set_list(&self, key: &str, items: &[String], ttl: Option<usize>) -> Fallible {
+        self.with_entries(key, |entries| {
+            if items.len() == 0 {
+                entries.remove(key);
+            } else {
+                entries.insert(key.to_owned(), Entry::new(Value::List(items.to_vec()), ttl));
+            }
+        });
+        Ok(())
+    }
+    async fn get_list(&self, key: &str) -> Fallible<Vec<String>> {
+        Ok(self.with_entries(key, |entries| match entries.get(key) {
+            Some(Entry {
+                value: Value::List(list),
+                ..
+            }) => list.clone(),
+            _ => Vec::new(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 不必等待真實時間，直接讓鍵逾時
+    fn expire_now(store: &MemoryKvStore, key: &str) {
+        let mut entries = store.entries.lock().unwrap();
+        entries.get_mut(key).unwrap().expire_at = Some(Instant::now());
+    }
+
+    #[tokio::test]
+    async fn test_ttl() -> Fallible {
+        let store = MemoryKvStore::new();
+        store.set("a:1", "1", Some(60)).await?;
+        store.set("a:2", "2", None).await?;
+        store.set("b:1", "3", Some(60)).await?;
+        assert_eq!(store.get("a:1").await?, Some("1".to_owned()));
+        let mut keys = store.keys("a:").await?;
+        keys.sort();
+        assert_eq!(keys, vec!["a:1".to_owned(), "a:2".to_owned()]);
+
+        expire_now(&store, "a:1");
+        assert_eq!(store.get("a:1").await?, None);
+        assert_eq!(store.keys("a:").await?, vec!["a:2".to_owned()]);
+
+        // 逾時後的鍵也不出現在 keys 中
+        expire_now(&store, "b:1");
+        assert!(store.keys("b:").await?.is_empty());
+
+        store.expire("a:2", 60).await?;
+        assert_eq!(store.get("a:2").await?, Some("2".to_owned()));
+        store.del("a:2").await?;
+        assert_eq!(store.get("a:2").await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_if_absent_or_eq() -> Fallible {
+        let store = MemoryKvStore::new();
+        assert!(store.set_if_absent_or_eq("lock", "甲", 60).await?);
+        // 同值續約
+        assert!(store.set_if_absent_or_eq("lock", "甲", 3600).await?);
+        let expire_at = store.entries.lock().unwrap()["lock"].expire_at.unwrap();
+        assert!(expire_at > Instant::now() + Duration::from_secs(60));
+        assert_eq!(store.get("lock").await?, Some("甲".to_owned()));
+        // 不同值被拒
+        assert!(!store.set_if_absent_or_eq("lock", "乙", 60).await?);
+        assert_eq!(store.get("lock").await?, Some("甲".to_owned()));
+        // 逾時後可被接手
+        expire_now(&store, "lock");
+        assert!(store.set_if_absent_or_eq("lock", "乙", 60).await?);
+        assert_eq!(store.get("lock").await?, Some("乙".to_owned()));
+        // 非字串的鍵不可寫入
+        store
+            .set_hash("hash", &[("k".to_owned(), "v".to_owned())])
+            .await?;
+        assert!(!store.set_if_absent_or_eq("hash", "甲", 60).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hash() -> Fallible {
+        let store = MemoryKvStore::new();
+        assert!(store.get_hash("hash").await?.is_empty());
+        assert_eq!(store.get_hash_field("hash", "a").await?, None);
+
+        let fields = vec![
+            ("a".to_owned(), "1".to_owned()),
+            ("b".to_owned(), "2".to_owned()),
+        ];
+        store.set_hash("hash", &fields).await?;
+        assert_eq!(
+            store.get_hash("hash").await?,
+            fields.iter().cloned().collect::<HashMap<_, _>>()
+        );
+        assert_eq!(
+            store.get_hash_field("hash", "b").await?,
+            Some("2".to_owned())
+        );
+        assert_eq!(store.get_hash_field("hash", "c").await?, None);
+        // 以雜湊表讀字串鍵得不到東西
+        assert_eq!(store.get("hash").await?, None);
+
+        // 整個替換
+        store
+            .set_hash("hash", &[("c".to_owned(), "3".to_owned())])
+            .await?;
+        assert_eq!(store.get_hash_field("hash", "a").await?, None);
+        assert_eq!(
+            store.get_hash_field("hash", "c").await?,
+            Some("3".to_owned())
+        );
+
+        // 空表等同刪除
+        store.set_hash("hash", &[]).await?;
+        assert!(store.keys("hash").await?.is_empty());
+        Ok(())
+    }
+}
@@ -1,20 +1,85 @@
-use crate::config;
+use crate::config::{get_config, KvStoreKind};
 use crate::custom_error::Fallible;
-use redis::{aio::Connection, Client};
+use async_trait::async_trait;
 use state::Storage;
+use std::collections::HashMap;
 
-static CLIENT: Storage<Client> = Storage::new();
+static KV_STORE: Storage<Box<dyn KvStore>> = Storage::new();
+
+/// 短期資料的鍵值存放處，值一律以字串存放，由各模組自行轉換
+///
+/// `ttl` 皆以秒計，逾時的鍵視同不存在
+#[async_trait]
+pub trait KvStore: Send + Sync {
+    async fn get(&self, key: &str) -> Fallible<Option<String>>;
+    /// `ttl` 爲 None 則永不過期
+    async fn set(&self, key: &str, value: &str, ttl: Option<usize>) -> Fallible;
+    async fn expire(&self, key: &str, ttl: usize) -> Fallible;
+    async fn del(&self, key: &str) -> Fallible;
+    /// 鍵不存在或其值已是 `value` 時寫入並重設存活時間，回傳是否寫入
+    async fn set_if_absent_or_eq(&self, key: &str, value: &str, ttl: usize) -> Fallible<bool>;
+    /// 以 `prefix` 開頭的所有鍵
+    async fn keys(&self, prefix: &str) -> Fallible<Vec<String>>;
+    /// 整個替換雜湊表
+    async fn set_hash(&self, key: &str, fields: &[(String, String)]) -> Fallible;
+    async fn get_hash(&self, key: &str) -> Fallible<HashMap<String, String>>;
+    async fn get_hash_field(&self, key: &str, field: &str) -> Fallible<Option<String>>;
+    /// 整個替換列表，空列表等同刪除
+    async fn set_list(&self, key: &str, items: &[String], ttl: Option<usize>) -> Fallible;
+    async fn get_list(&self, key: &str) -> Fallible<Vec<String>>;
+}
+
+pub fn new_store(kind: KvStoreKind) -> Fallible<Box<dyn KvStore>> {
+    match kind {
+        #[cfg(feature = "redis")]
+        KvStoreKind::Redis => Ok(Box::new(redis_store::RedisKvStore::new(
+            &get_config().redis.host,
+        )?)),
+        #[cfg(not(feature = "redis"))]
+        KvStoreKind::Redis => {
+            log::warn!("未啓用 redis 功能，改存於記憶體");
+            Ok(Box::new(memory_store::MemoryKvStore::new()))
+        }
+        KvStoreKind::Memory => Ok(Box::new(memory_store::MemoryKvStore::new())),
+    }
+}
 
 pub async fn init() -> Fallible<()> {
-    let conf = config::get_config();
-    let client = Client::open(&*conf.redis.host)?;
-    assert!(CLIENT.set(client), "Redis 客戶端被重複創建");
+    init_with(get_config().redis.store).await
+}
+
+/// 不經設定檔指定存放處，供測試使用
+pub async fn init_with(kind: KvStoreKind) -> Fallible<()> {
+    log::info!("短期資料存放於 {:?}", kind);
+    assert!(KV_STORE.set(new_store(kind)?), "KvStore 被重複創建");
     Ok(())
 }
-pub async fn get_conn() -> Fallible<Connection> {
-    let conn = CLIENT.get().get_async_connection().await?;
-    Ok(conn)
+
+pub fn get_store() -> &'static dyn KvStore {
+    &**KV_STORE.get()
+}
+
+fn parse_map<K, V>(map: HashMap<String, String>) -> HashMap<K, V>
+where
+    K: std::str::FromStr + std::hash::Hash + Eq,
+    V: std::str::FromStr,
+{
+    map.into_iter()
+        .filter_map(|(k, v)| Some((k.parse().ok()?, v.parse().ok()?)))
+        .collect()
 }
+fn to_pairs<K: ToString, V: ToString>(map: &HashMap<K, V>) -> Vec<(String, String)> {
+    map.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+mod memory_store;
+#[cfg(feature = "redis")]
+mod redis_store;
 
 pub mod board_pop;
+pub mod hot_articles;
 pub mod hot_boards;
+pub mod job;
+pub mod session;
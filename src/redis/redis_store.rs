@@ -0,0 +1,112 @@
+use super::KvStore;
+use crate::custom_error::Fallible;
+use async_trait::async_trait;
+use redis::{aio::Connection, AsyncCommands, Client};
+use std::collections::HashMap;
+
+pub struct RedisKvStore {
+    client: Client,
+}
+
+impl RedisKvStore {
+    pub fn new(host: &str) -> Fallible<Self> {
+        Ok(RedisKvStore {
+            client: Client::open(host)?,
+        })
+    }
+    async fn get_conn(&self) -> Fallible<Connection> {
+        let conn = self.client.get_async_connection().await?;
+        Ok(conn)
+    }
+}
+
+#[async_trait]
+impl KvStore for RedisKvStore {
+    async fn get(&self, key: &str) -> Fallible<Option<String>> {
+        let mut conn = self.get_conn().await?;
+        Ok(conn.get(key).await?)
+    }
+    async fn set(&self, key: &str, value: &str, ttl: Option<usize>) -> Fallible {
+        let mut conn = self.get_conn().await?;
+        match ttl {
+            Some(ttl) => conn.set_ex::<_, _, ()>(key, value, ttl).await?,
+            None => conn.set::<_, _, ()>(key, value).await?,
+        }
+        Ok(())
+    }
+    async fn expire(&self, key: &str, ttl: usize) -> Fallible {
+        let mut conn = self.get_conn().await?;
+        conn.expire::<_, ()>(key, ttl).await?;
+        Ok(())
+    }
+    async fn del(&self, key: &str) -> Fallible {
+        let mut conn = self.get_conn().await?;
+        conn.del::<_, ()>(key).await?;
+        Ok(())
+    }
+    async fn set_if_absent_or_eq(&self, key: &str, value: &str, ttl: usize) -> Fallible<bool> {
+        let mut conn = self.get_conn().await?;
+        let written: bool = redis::Script::new(
+            r"
+            if redis.call('GET', KEYS[1]) == ARGV[1] then
+                redis.call('EXPIRE', KEYS[1], ARGV[2])
+                return 1
+            end
+            if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'EX', ARGV[2]) then
+                return 1
+            end
+            return 0
+            ",
+        )
+        .key(key)
+        .arg(value)
+        .arg(ttl)
+        .invoke_async(&mut conn)
+        .await?;
+        Ok(written)
+    }
+    async fn keys(&self, prefix: &str) -> Fallible<Vec<String>> {
+        let mut conn = self.get_conn().await?;
+        let mut iter = conn.scan_match::<_, String>(format!("{}*", prefix)).await?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+    async fn set_hash(&self, key: &str, fields: &[(String, String)]) -> Fallible {
+        let mut conn = self.get_conn().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(key);
+        if fields.len() > 0 {
+            pipe.hset_multiple(key, fields);
+        }
+        pipe.query_async::<_, ()>(&mut conn).await?;
+        Ok(())
+    }
+    async fn get_hash(&self, key: &str) -> Fallible<HashMap<String, String>> {
+        let mut conn = self.get_conn().await?;
+        Ok(conn.hgetall(key).await?)
+    }
+    async fn get_hash_field(&self, key: &str, field: &str) -> Fallible<Option<String>> {
+        let mut conn = self.get_conn().await?;
+        Ok(conn.hget(key, field).await?)
+    }
+    async fn set_list(&self, key: &str, items: &[String], ttl: Option<usize>) -> Fallible {
+        let mut conn = self.get_conn().await?;
+        let mut pipe = redis::pipe();
+        pipe.atomic().del(key);
+        if items.len() > 0 {
+            pipe.rpush(key, items);
+            if let Some(ttl) = ttl {
+                pipe.expire(key, ttl);
+            }
+        }
+        pipe.query_async::<_, ()>(&mut conn).await?;
+        Ok(())
+    }
+    async fn get_list(&self, key: &str) -> Fallible<Vec<String>> {
+        let mut conn = self.get_conn().await?;
+        Ok(conn.lrange(key, 0, -1).await?)
+    }
+}
@@ -0,0 +1,47 @@
+use super::get_store;
+use crate::custom_error::Fallible;
+
+/// 登入狀態的存活秒數，每次使用時重新計算
+// TODO: 設置到 config.toml
+const SESSION_TTL: usize = 60 * 60 * 24 * 7;
+
+fn key(token: &str) -> String {
+    format!("session:{}", token)
+}
+
+pub async fn create(token: &str, user_id: i64) -> Fallible {
+    get_store()
+        .set(&key(token), &user_id.to_string(), Some(SESSION_TTL))
+        .await
+}
+
+/// 查詢 token 對應的使用者並延長其存活時間
+pub async fn get(token: &str) -> Fallible<Option<i64>> {
+    let store = get_store();
+    let user_id = store.get(&key(token)).await?.and_then(|id| id.parse().ok());
+    match user_id {
+        Some(_) => store.expire(&key(token), SESSION_TTL).await?,
+        None => return migrate_legacy(token).await,
+    }
+    Ok(user_id)
+}
+
+/// 舊版直接以 token 爲鍵，查不到時改查舊鍵並搬到新鍵，以免更新後所有人被登出
+// TODO: 舊鍵最多存活 SESSION_TTL ，之後可移除
+async fn migrate_legacy(token: &str) -> Fallible<Option<i64>> {
+    // token 來自 cookie ，只接受 32 位元組的 base64 ，以免被拿來讀取其它鍵
+    let is_token = token.len() == 44
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=');
+    if !is_token {
+        return Ok(None);
+    }
+    let store = get_store();
+    let user_id: Option<i64> = store.get(token).await?.and_then(|id| id.parse().ok());
+    if let Some(user_id) = user_id {
+        create(token, user_id).await?;
+        store.del(token).await?;
+    }
+    Ok(user_id)
+}
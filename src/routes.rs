@@ -2,13 +2,15 @@ use crate::{
     api::api_impl,
     api::api_trait::RootQueryRouter,
     api::query,
-    custom_error::{Contextable, ErrorCode, Fallible},
-    db, Ctx,
+    custom_error::{Contextable, Error, ErrorCode, Fallible},
+    db, util, Ctx,
 };
+use futures::future::join_all;
 use futures::stream::StreamExt;
 use futures::FutureExt;
 use hyper::{body::Bytes, HeaderMap};
-use hyper::{Body, Response, StatusCode};
+use hyper::{header, Body, Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use warp::Filter;
 
@@ -18,71 +20,359 @@ fn not_found() -> Response<Body> {
     not_found
 }
 
-fn to_response(resp: Fallible<Response<Body>>) -> Response<Body> {
-    match resp {
-        Ok(body) => body,
-        Err(err) => {
-            let err_msg = serde_json::to_string(&err).unwrap_or(String::default());
-            let mut err_body = Response::new(Body::from(err_msg));
-            *err_body.status_mut() = StatusCode::BAD_REQUEST;
-            err_body
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+fn new_request_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+fn status_of(err: &Error) -> StatusCode {
+    let code = match err {
+        Error::LogicError { code, .. } => code,
+        _ => return StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    match code {
+        ErrorCode::NeedLogin => StatusCode::UNAUTHORIZED,
+        ErrorCode::PermissionDenied | ErrorCode::BannedFromBoard => StatusCode::FORBIDDEN,
+        ErrorCode::NotFound(..) => StatusCode::NOT_FOUND,
+        ErrorCode::DuplicateRegister
+        | ErrorCode::AlreadyInParty
+        | ErrorCode::ElectionClosed
+        | ErrorCode::PollClosed
+        | ErrorCode::PinLimitExceeded(..) => StatusCode::CONFLICT,
+        ErrorCode::ParsingJson => StatusCode::BAD_REQUEST,
+        ErrorCode::ForceValidate(..)
+        | ErrorCode::InsufficientPartyEnergy(..)
+        | ErrorCode::InsufficientPartyMembers(..)
+        | ErrorCode::InvalidImage(..)
+        | ErrorCode::InvalidVote(..)
+        | ErrorCode::OutOfRange(..) => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorCode::BatchTooLarge(..) => StatusCode::PAYLOAD_TOO_LARGE,
+        ErrorCode::UnImplemented => StatusCode::NOT_IMPLEMENTED,
+        // 不屬於以上任何一類，多半是伺服器端的問題
+        ErrorCode::Other(..) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorInfo<'a> {
+    code: &'static str,
+    status: u16,
+    message: String,
+    request_id: &'a str,
+}
+
+/// 錯誤回應的內容。 `Err` 欄位讓前端仍能把回應當成 Result 解析，
+/// `error` 則提供穩定的錯誤代碼與請求編號
+#[derive(Serialize)]
+struct ErrorEnvelope<'a> {
+    #[serde(rename = "Err")]
+    err: &'a Error,
+    error: ErrorInfo<'a>,
+}
+
+/// 邏輯錯誤原樣回傳；其它錯誤可能含有資料庫等內部細節，只記在日誌，回應中僅留請求編號
+fn error_body(err: Error, request_id: &str) -> (StatusCode, String) {
+    let status = status_of(&err);
+    let (err, code, message) = match err {
+        Error::LogicError { ref code, .. } => {
+            log::debug!("請求 {} 失敗： {}", request_id, err);
+            let (name, message) = (code.name(), code.to_string());
+            (err, name, message)
+        }
+        err => {
+            log::error!("請求 {} 發生內部錯誤： {}", request_id, err);
+            let message = "伺服器內部錯誤".to_owned();
+            let hidden = Error::new_internal(format!("{}，請求編號 {}", message, request_id));
+            (hidden, "internal_error", message)
         }
+    };
+    let envelope = ErrorEnvelope {
+        err: &err,
+        error: ErrorInfo {
+            code,
+            status: status.as_u16(),
+            message,
+            request_id,
+        },
+    };
+    let body = serde_json::to_string(&envelope).unwrap_or(String::default());
+    (status, body)
+}
+
+fn error_response(err: Error, request_id: &str) -> Response<Body> {
+    let (status, body) = error_body(err, request_id);
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = status;
+    resp.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    resp
+}
+
+fn to_response(resp: Fallible<Response<Body>>, request_id: &str) -> Response<Body> {
+    let mut resp = match resp {
+        Ok(body) => body,
+        Err(err) => error_response(err, request_id),
+    };
+    if let Ok(request_id) = request_id.parse::<header::HeaderValue>() {
+        resp.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+    }
+    resp
+}
+
+#[derive(Deserialize)]
+struct AvatarQuery {
+    size: Option<u32>,
+}
+
+fn etag_matches(if_none_match: &Option<String>, etag: &str) -> bool {
+    match if_none_match {
+        Some(tags) => tags.split(',').any(|tag| tag.trim() == etag),
+        None => false,
     }
 }
 
-async fn _handle_avatar(user_name: String) -> Fallible<Response<Body>> {
-    match percent_encoding::percent_decode(user_name.as_bytes()).decode_utf8() {
-        Ok(user_name) => {
-            log::trace!("請求大頭貼： {}", user_name);
-            Ok(Response::new(Body::from(
-                db::avatar::get_avatar(&user_name).await?,
-            )))
+fn with_etag(
+    mut resp: Response<Body>,
+    etag: &str,
+    content_type: &str,
+    cache_control: &'static str,
+) -> Fallible<Response<Body>> {
+    let headers = resp.headers_mut();
+    headers.insert(header::CONTENT_TYPE, content_type.parse()?);
+    headers.insert(header::ETAG, etag.parse()?);
+    headers.insert(header::CACHE_CONTROL, cache_control.parse()?);
+    Ok(resp)
+}
+
+fn not_modified() -> Response<Body> {
+    let mut resp = Response::default();
+    *resp.status_mut() = StatusCode::NOT_MODIFIED;
+    resp
+}
+
+// 大頭貼隨時可能更換，每次都以 ETag 確認
+const AVATAR_CACHE_CONTROL: &str = "no-cache";
+
+fn default_avatar_response(size: u32, if_none_match: &Option<String>) -> Fallible<Response<Body>> {
+    let data = util::avatar::default_avatar(size);
+    let etag = util::avatar::etag(data);
+    let resp = if etag_matches(if_none_match, &etag) {
+        not_modified()
+    } else {
+        Response::new(Body::from(data))
+    };
+    with_etag(resp, &etag, "image/png", AVATAR_CACHE_CONTROL)
+}
+
+async fn _handle_avatar(
+    user_name: String,
+    query: AvatarQuery,
+    if_none_match: Option<String>,
+) -> Fallible<Response<Body>> {
+    let user_name = match percent_encoding::percent_decode(user_name.as_bytes()).decode_utf8() {
+        Ok(user_name) => user_name,
+        Err(_) => return Ok(not_found()),
+    };
+    log::trace!("請求大頭貼： {}", user_name);
+    let size = util::avatar::standard_size(query.size);
+    let image = match db::avatar::get_avatar(&user_name).await? {
+        Some(image) => image,
+        None => return default_avatar_response(size, &if_none_match),
+    };
+    // 鍵即內容雜湊，加上邊長就能當 ETag ，不必讀出圖片
+    let etag = format!("\"{}-{}\"", image.blob_key, size);
+    if etag_matches(&if_none_match, &etag) {
+        return with_etag(not_modified(), &etag, "image/png", AVATAR_CACHE_CONTROL);
+    }
+    let store = db::blob::get_store();
+    if size == util::avatar::MAX_AVATAR_SIZE {
+        let body = store.stream(&image.blob_key).await?;
+        return with_etag(
+            Response::new(body),
+            &etag,
+            &image.content_type,
+            AVATAR_CACHE_CONTROL,
+        );
+    }
+    if let Some(data) = util::avatar::get_resized(&image.blob_key, size) {
+        return with_etag(
+            Response::new(Body::from(data)),
+            &etag,
+            "image/png",
+            AVATAR_CACHE_CONTROL,
+        );
+    }
+    let data = store.get(&image.blob_key).await?;
+    match tokio::task::spawn_blocking(move || util::avatar::resize(&data, size)).await? {
+        Ok(data) => {
+            let data = util::avatar::cache_resized(&image.blob_key, size, data);
+            with_etag(
+                Response::new(Body::from(data)),
+                &etag,
+                "image/png",
+                AVATAR_CACHE_CONTROL,
+            )
+        }
+        Err(err) => {
+            log::warn!("無法解碼 {} 的大頭貼，改用預設： {}", user_name, err);
+            default_avatar_response(size, &if_none_match)
         }
-        Err(_) => Ok(not_found()),
     }
 }
 
-async fn handle_avatar(user_name: String) -> Result<impl warp::Reply, Infallible> {
-    Ok(to_response(_handle_avatar(user_name).await))
+async fn handle_avatar(
+    user_name: String,
+    query: AvatarQuery,
+    if_none_match: Option<String>,
+) -> Result<impl warp::Reply, Infallible> {
+    Ok(to_response(
+        _handle_avatar(user_name, query, if_none_match).await,
+        &new_request_id(),
+    ))
+}
+
+async fn _handle_image(id: i64, if_none_match: Option<String>) -> Fallible<Response<Body>> {
+    log::trace!("請求圖片： {}", id);
+    let image = db::image::get(id).await?;
+    let etag = format!("\"{}\"", image.blob_key);
+    let resp = if etag_matches(&if_none_match, &etag) {
+        not_modified()
+    } else {
+        Response::new(db::blob::get_store().stream(&image.blob_key).await?)
+    };
+    // 圖片上傳後不會再變
+    with_etag(
+        resp,
+        &etag,
+        &image.content_type,
+        "public, max-age=31536000, immutable",
+    )
+}
+
+async fn handle_image(
+    id: i64,
+    if_none_match: Option<String>,
+) -> Result<impl warp::Reply, Infallible> {
+    Ok(to_response(
+        _handle_image(id, if_none_match).await,
+        &new_request_id(),
+    ))
 }
 
-async fn run_chitin(query: query::RootQuery, context: &mut Ctx) -> Fallible<String> {
-    log::info!("請求： {:?}", query);
+/// chitin 把 api 的錯誤包在成功的回應中，這裡取出來改由 `error_response` 處理
+async fn run_chitin(
+    query: query::RootQuery,
+    context: &mut Ctx,
+    request_id: &str,
+) -> Fallible<String> {
+    log::info!("請求 {}： {:?}", request_id, query);
     let root: api_impl::RootQueryRouter = Default::default();
     let resp = root
         .handle(context, query.clone())
         .await
         .context("api 物件序列化錯誤（極異常！）")?;
 
-    if let Some(err) = &resp.1 {
+    if let Some(err) = resp.1 {
         log::warn!("執行 api {:?} 時發生錯誤： {}", query, err);
+        return Err(err);
     }
     Ok(resp.0)
 }
 
-async fn _handle_api(body: Bytes, headers: HeaderMap) -> Fallible<Response<Body>> {
-    let mut context = Ctx {
-        headers: headers,
-        resp: Response::new(String::new()),
-    };
+/// 一次批次請求最多能包含的 api 呼叫數
+const MAX_BATCH: usize = 32;
+
+/// 批次中的各請求並行執行，共用同一份 `Ctx` 的 cookie ，看得到其它請求已設下的登入狀態，
+/// 但並行的請求之間不保證先後。
+/// 回傳與請求同序的陣列，單一請求的錯誤放在陣列中對應的位置，不影響其它請求
+async fn handle_batch(
+    body: Bytes,
+    headers: HeaderMap,
+    request_id: &str,
+) -> Fallible<Response<Body>> {
+    let queries: Vec<query::RootQuery> = serde_json::from_slice(&body.to_vec()).map_err(|e| {
+        ErrorCode::ParsingJson.context(format!("解析批次請求 {:?} 錯誤 {}", body, e,))
+    })?;
+    if queries.len() > MAX_BATCH {
+        return Err(ErrorCode::BatchTooLarge(MAX_BATCH).into());
+    }
+    let context = Ctx::new(headers);
+    let results = join_all(queries.into_iter().enumerate().map(|(i, query)| {
+        let mut context = context.share();
+        async move {
+            let request_id = format!("{}-{}", request_id, i);
+            let body = match run_chitin(query, &mut context, &request_id).await {
+                Ok(resp) => resp,
+                Err(err) => error_body(err, &request_id).1,
+            };
+            (body, context.resp.into_parts().0.headers)
+        }
+    }))
+    .await;
+
+    let mut resp = Response::new(Body::empty());
+    let mut bodies = Vec::new();
+    for (body, headers) in results.into_iter() {
+        bodies.push(body);
+        resp.headers_mut().extend(headers);
+    }
+    // 各請求的 Set-Cookie 可能重複或過時，改由共用的 cookie 重寫
+    context.write_cookies(resp.headers_mut())?;
+    *resp.body_mut() = Body::from(format!("[{}]", bodies.join(",")));
+    Ok(resp)
+}
+
+async fn _handle_api(
+    body: Bytes,
+    headers: HeaderMap,
+    request_id: &str,
+) -> Fallible<Response<Body>> {
+    // 以陣列送來的是批次請求
+    if body.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[') {
+        return handle_batch(body, headers, request_id).await;
+    }
+
+    let mut context = Ctx::new(headers);
 
     let query: query::RootQuery = serde_json::from_slice(&body.to_vec())
         .map_err(|e| ErrorCode::ParsingJson.context(format!("解析請求 {:?} 錯誤 {}", body, e,)))?;
 
-    let resp = run_chitin(query, &mut context).await?;
-
-    context.resp.body_mut().push_str(&resp);
-    Ok(context.resp.map(|s| Body::from(s)))
+    match run_chitin(query, &mut context, request_id).await {
+        Ok(resp) => {
+            context.resp.body_mut().push_str(&resp);
+            Ok(context.resp.map(|s| Body::from(s)))
+        }
+        Err(err) => {
+            // 保留 api 執行中設下的標頭，例如清除失效登入的 cookie
+            let mut resp = error_response(err, request_id);
+            resp.headers_mut()
+                .extend(context.resp.into_parts().0.headers);
+            Ok(resp)
+        }
+    }
 }
 
 async fn handle_api(body: Bytes, headers: HeaderMap) -> Result<impl warp::Reply, Infallible> {
-    Ok(to_response(_handle_api(body, headers).await))
+    let request_id = new_request_id();
+    Ok(to_response(
+        _handle_api(body, headers, &request_id).await,
+        &request_id,
+    ))
 }
 
 pub fn get_routes(
 ) -> Fallible<impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone> {
     // 設定前端
-    let avatar = warp::path!("avatar" / String).and_then(handle_avatar);
+    let avatar = warp::path!("avatar" / String)
+        .and(warp::query::<AvatarQuery>())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and_then(handle_avatar);
+    let image = warp::path!("image" / i64)
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and_then(handle_image);
     let chat = warp::path!("chat").and(warp::ws()).map(|ws: warp::ws::Ws| {
         ws.on_upgrade(|websocket| {
             let (tx, rx) = websocket.split();
@@ -98,10 +388,116 @@ pub fn get_routes(
         .and(warp::header::headers_cloned())
         .and_then(handle_api);
 
-    let gets = warp::get().and(avatar.or(chat));
+    let gets = warp::get().and(avatar.or(image).or(chat));
 
     let posts = warp::post().and(api);
 
     let routes = gets.or(posts);
     Ok(routes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custom_error::DataType;
+    use serde_json::Value;
+
+    #[test]
+    fn test_status_of() {
+        let table = vec![
+            (ErrorCode::NeedLogin, StatusCode::UNAUTHORIZED),
+            (ErrorCode::PermissionDenied, StatusCode::FORBIDDEN),
+            (ErrorCode::BannedFromBoard, StatusCode::FORBIDDEN),
+            (
+                ErrorCode::NotFound(DataType::Category, "新聞".to_owned()),
+                StatusCode::NOT_FOUND,
+            ),
+            (ErrorCode::DuplicateRegister, StatusCode::CONFLICT),
+            (ErrorCode::AlreadyInParty, StatusCode::CONFLICT),
+            (ErrorCode::ElectionClosed, StatusCode::CONFLICT),
+            (ErrorCode::PollClosed, StatusCode::CONFLICT),
+            (ErrorCode::PinLimitExceeded(3), StatusCode::CONFLICT),
+            (ErrorCode::ParsingJson, StatusCode::BAD_REQUEST),
+            (
+                ErrorCode::ForceValidate(vec![]),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ErrorCode::InsufficientPartyEnergy(10),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ErrorCode::InsufficientPartyMembers(10),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ErrorCode::InvalidImage(String::new()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ErrorCode::InvalidVote(String::new()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (
+                ErrorCode::OutOfRange(String::new()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+            (ErrorCode::BatchTooLarge(32), StatusCode::PAYLOAD_TOO_LARGE),
+            (ErrorCode::UnImplemented, StatusCode::NOT_IMPLEMENTED),
+            (
+                ErrorCode::Other(String::new()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+        for (code, status) in table.into_iter() {
+            let name = code.name();
+            assert_eq!(status_of(&code.into()), status, "{}", name);
+        }
+        assert_eq!(
+            status_of(&Error::new_op("操作錯誤")),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            status_of(&Error::new_internal("內部錯誤")),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_logic_error_body() {
+        let err = ErrorCode::PinLimitExceeded(3).context("置頂失敗");
+        let (status, body) = error_body(err, "abc");
+        assert_eq!(status, StatusCode::CONFLICT);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body["error"],
+            serde_json::json!({
+                "code": "pin_limit_exceeded",
+                "status": 409,
+                "message": "置頂文章最多 3 篇",
+                "request_id": "abc",
+            })
+        );
+        // 前端仍能當成 Result 解析
+        assert_eq!(
+            body["Err"]["LogicError"]["code"],
+            serde_json::json!({ "PinLimitExceeded": 3 })
+        );
+    }
+
+    #[test]
+    fn test_internal_error_hidden() {
+        let err = Error::new_internal("資料庫密碼是 hunter2");
+        let (status, body) = error_body(err, "abc");
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!body.contains("hunter2"));
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["error"]["code"], "internal_error");
+        assert_eq!(body["error"]["status"], 500);
+        assert_eq!(body["error"]["request_id"], "abc");
+        assert!(body["Err"]["InternalError"]["msg"][0]
+            .as_str()
+            .unwrap()
+            .contains("abc"));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::api::model::NotificationKind;
+use crate::config::get_config;
+use crate::custom_error::{Contextable, Fallible};
+use crate::db;
+use chrono::{Duration as ChronoDuration, Utc};
+
+/// 兩次選舉開始的間隔
+const ELECTION_PERIOD_DAYS: i64 = 30;
+const VOTING_DAYS: i64 = 3;
+
+pub async fn run() -> Fallible {
+    open_elections().await?;
+    tally_elections().await
+}
+
+async fn open_elections() -> Fallible {
+    let now = Utc::now();
+    let opened = db::election::open_due(
+        now - ChronoDuration::days(ELECTION_PERIOD_DAYS),
+        now + ChronoDuration::days(VOTING_DAYS),
+    )
+    .await
+    .context("舉辦選舉失敗")?;
+    if !opened.is_empty() {
+        log::info!("舉辦選舉：{:?}", opened);
+    }
+    Ok(())
+}
+
+async fn tally_elections() -> Fallible {
+    let due = db::election::get_due().await.context("取待開票選舉失敗")?;
+    for election_id in due.into_iter() {
+        // 單場開票失敗不影響其它看板，下一輪再試
+        if let Err(err) = tally(election_id).await {
+            log::warn!("選舉 {} 開票失敗：{}", election_id, err);
+        }
+    }
+    Ok(())
+}
+
+async fn tally(election_id: i64) -> Fallible {
+    let weight = get_config().party.election_energy_weight;
+    let result = match db::election::tally(election_id, weight).await? {
+        Some(result) => result,
+        None => return Ok(()),
+    };
+    log::info!(
+        "看板 {} 選舉 {} 開票，{} 執政{}",
+        result.board_id,
+        election_id,
+        result.winner_party_id,
+        if result.changed {
+            "（政黨輪替）"
+        } else {
+            ""
+        }
+    );
+    // 開票已提交，不會重跑，單一通知失敗只記錄，其餘照發
+    for user_id in result.recipients.into_iter() {
+        if let Err(err) = super::notification::create(
+            user_id,
+            NotificationKind::ElectionResult,
+            None,
+            Some(result.board_id),
+            None,
+            None,
+        )
+        .await
+        {
+            log::warn!("通知 {} 選舉 {} 結果失敗：{}", user_id, election_id, err);
+        }
+    }
+    Ok(())
+}
@@ -18,12 +18,14 @@ pub async fn query_graph(
     count: usize,
     article_id: i64,
     category_set: Option<&[String]>,
+    tag_set: Option<&[String]>,
     family_filer: &FamilyFilter,
 ) -> Fallible<Graph> {
     log::debug!(
-        "詢問鳥瞰圖，中心點為 {}，分類為 {:?}，忽略分類族{:?}",
+        "詢問鳥瞰圖，中心點為 {}，分類為 {:?}，標籤為 {:?}，忽略分類族{:?}",
         article_id,
         category_set,
+        tag_set,
         family_filer
     );
     let mut articles_to_expand = vec![article_id];
@@ -44,8 +46,8 @@ pub async fn query_graph(
         let mut articles_next = vec![];
         for id in articles_to_expand.into_iter() {
             let (bondee, bonder) = tokio::join!(
-                db::article::get_bondee_meta(id, category_set, family_filer),
-                db::article::get_bonder_meta(id, category_set, family_filer)
+                db::article::get_bondee_meta(id, category_set, tag_set, family_filer),
+                db::article::get_bonder_meta(id, category_set, tag_set, family_filer)
             );
             let (bondee, bonder) = (bondee?, bonder?);
             macro_rules! insert {
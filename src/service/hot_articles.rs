@@ -0,0 +1,80 @@
+use super::hot_boards::{tau, WINDOWS};
+use crate::api::model::HotWindow;
+use crate::custom_error::{Contextable, Fallible};
+use crate::db;
+use crate::db::article_statistics::HotCandidate;
+use crate::redis::hot_articles;
+use chrono::{Duration as ChronoDuration, Utc};
+use std::collections::HashMap;
+
+const MAX_HOT_ARTICLES: usize = 100;
+/// 只考慮 HORIZON 個 tau 內發表的文章，更早的衰減後已微不足道
+const HORIZON: f64 = 5.0;
+
+// 各項統計對熱度的權重
+const ENERGY_WEIGHT: f64 = 1.0;
+const REPLY_WEIGHT: f64 = 2.0;
+const SATELLITE_WEIGHT: f64 = 0.5;
+const FAVORITE_WEIGHT: f64 = 3.0;
+
+fn score(article: &HotCandidate, tau: f64) -> f64 {
+    let age = (Utc::now() - article.create_time).num_seconds().max(0) as f64;
+    let activity = article.energy as f64 * ENERGY_WEIGHT
+        + article.replies as f64 * REPLY_WEIGHT
+        + article.satellite_replies as f64 * SATELLITE_WEIGHT
+        + article.favorites as f64 * FAVORITE_WEIGHT;
+    // 加一讓沒有互動的新文章也依時間排序，負鍵能的文章則沉底
+    (activity.max(0.0) + 1.0) * (-age / tau).exp()
+}
+
+fn top(mut scored: Vec<(i64, f64)>) -> Vec<i64> {
+    scored.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    scored
+        .into_iter()
+        .take(MAX_HOT_ARTICLES)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+pub async fn run() -> Fallible {
+    log::info!("開始計算熱門文章");
+    for &window in WINDOWS.iter() {
+        compute_window(window).await?;
+    }
+    Ok(())
+}
+
+async fn compute_window(window: HotWindow) -> Fallible {
+    let tau = tau(window);
+    let since = Utc::now() - ChronoDuration::seconds((tau * HORIZON) as i64);
+    let candidates = db::article_statistics::get_hot_candidates(since)
+        .await
+        .context("取熱門文章候選失敗")?;
+    let mut all = Vec::new();
+    let mut boards = HashMap::<i64, Vec<(i64, f64)>>::new();
+    for article in candidates.iter() {
+        let score = score(article, tau);
+        all.push((article.id, score));
+        boards
+            .entry(article.board_id)
+            .or_default()
+            .push((article.id, score));
+    }
+    // 多留幾輪，計算偶爾失敗時不致讓列表消失
+    let ttl = super::job::get_interval(super::job::HOT_ARTICLES) as usize * 5;
+    let all = top(all);
+    log::debug!("{:?} 熱門文章結果：{:?}", window, all);
+    hot_articles::set_hot_articles(window, None, &all, ttl)
+        .await
+        .context("設定熱門文章失敗")?;
+    for (board_id, scored) in boards.into_iter() {
+        hot_articles::set_hot_articles(window, Some(board_id), &top(scored), ttl)
+            .await
+            .context("設定看板熱門文章失敗")?;
+    }
+    Ok(())
+}
+
+pub async fn get_hot_articles(window: HotWindow, board_id: Option<i64>) -> Fallible<Vec<i64>> {
+    hot_articles::get_hot_articles(window, board_id).await
+}
@@ -1,33 +1,79 @@
+use crate::api::model::HotWindow;
 use crate::custom_error::{Contextable, Fallible};
+use crate::db;
 use crate::redis::{board_pop, hot_boards};
-use std::time::Duration;
-use tokio::time::interval;
+use chrono::Utc;
+use std::collections::HashMap;
 
-const INTERVAL: u64 = 10;
 const MAX_HOT_BOARDS: usize = 100;
+pub const WINDOWS: [HotWindow; 3] = [HotWindow::Now, HotWindow::Today, HotWindow::Week];
 
-pub async fn start() -> Fallible {
-    let mut interval = interval(Duration::from_secs(INTERVAL));
-    loop {
-        interval.tick().await;
-        compute_hot_boards().await?;
+// 各項活動對熱度的權重
+const POST_WEIGHT: f64 = 1.0;
+const BOND_WEIGHT: f64 = 0.5;
+const VISITOR_WEIGHT: f64 = 2.0;
+
+/// 衰減的時間常數（秒），經過 tau 秒後權重剩 1/e
+pub fn tau(window: HotWindow) -> f64 {
+    match window {
+        HotWindow::Now => 60.0 * 60.0,
+        HotWindow::Today => 8.0 * 60.0 * 60.0,
+        HotWindow::Week => 3.0 * 24.0 * 60.0 * 60.0,
     }
 }
 
-async fn compute_hot_boards() -> Fallible {
+pub async fn run() -> Fallible {
     log::info!("開始計算熱門看板");
-    let boards = board_pop::get_all_board_pop()
+    let presence = board_pop::count_presence()
         .await
         .context("取看板人氣失敗")?;
-    let mut boards: Vec<_> = boards.into_iter().collect();
-    boards.sort_by_key(|(_, pop)| -(*pop as i64));
+    for &window in WINDOWS.iter() {
+        compute_window(window, &presence).await?;
+    }
+    Ok(())
+}
+
+async fn compute_window(window: HotWindow, presence: &HashMap<i64, i64>) -> Fallible {
+    let tau = tau(window);
+    // 在場人數的指數移動平均，每輪以本輪人數補上衰減掉的部分。
+    // 依上次計算至今實際經過的時間衰減，失敗重試或換實例執行時仍正確
+    let now = Utc::now();
+    let (mut visitors, last_time) = hot_boards::get_visitors(window)
+        .await
+        .context("取看板訪客失敗")?;
+    let elapsed = match last_time {
+        Some(last_time) => (now - last_time).num_milliseconds().max(0) as f64 / 1000.0,
+        None => super::job::get_interval(super::job::HOT_BOARDS) as f64,
+    };
+    let decay = (-elapsed / tau).exp();
+    for v in visitors.values_mut() {
+        *v *= decay;
+    }
+    for (id, count) in presence.iter() {
+        *visitors.entry(*id).or_default() += *count as f64 * (1.0 - decay);
+    }
+    visitors.retain(|_, v| *v >= 0.01);
+    hot_boards::set_visitors(window, &visitors, now)
+        .await
+        .context("設定看板訪客失敗")?;
+
+    let mut scores: HashMap<i64, f64> = visitors
+        .iter()
+        .map(|(id, v)| (*id, v * VISITOR_WEIGHT))
+        .collect();
+    for activity in db::board::get_decayed_activity(tau).await?.into_iter() {
+        *scores.entry(activity.board_id).or_default() +=
+            activity.posts * POST_WEIGHT + activity.bonds * BOND_WEIGHT;
+    }
+    let mut boards: Vec<_> = scores.into_iter().collect();
+    boards.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
     let boards: Vec<_> = boards
         .into_iter()
         .take(MAX_HOT_BOARDS)
         .map(|(id, _)| id)
         .collect();
-    log::debug!("熱門看板結果：{:?}", boards);
-    hot_boards::set_hot_boards(&boards)
+    log::debug!("{:?} 熱門看板結果：{:?}", window, boards);
+    hot_boards::set_hot_boards(window, &boards)
         .await
         .context("設定熱門看板失敗")?;
     Ok(())
@@ -36,6 +82,6 @@ async fn compute_hot_boards() -> Fallible {
 pub async fn set_board_pop(user_id: i64, board_id: i64) -> Fallible {
     board_pop::set_board_pop(user_id, board_id).await
 }
-pub async fn get_hot_boards() -> Fallible<Vec<i64>> {
-    hot_boards::get_hot_boards().await
+pub async fn get_hot_boards(window: HotWindow) -> Fallible<Vec<i64>> {
+    hot_boards::get_hot_boards(window).await
 }
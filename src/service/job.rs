@@ -0,0 +1,137 @@
+use crate::config::get_config;
+use crate::custom_error::Fallible;
+use crate::redis::job::{self, JobStatus};
+use chrono::Utc;
+use futures::future::{join_all, BoxFuture, FutureExt};
+use rand::Rng;
+use std::time::Duration;
+
+pub const HOT_BOARDS: &'static str = "hot_boards";
+pub const HOT_ARTICLES: &'static str = "hot_articles";
+pub const ELECTION: &'static str = "election";
+
+pub struct Job {
+    pub name: &'static str,
+    /// 設定檔未指定時的執行間隔（秒）
+    default_interval: u64,
+    run: fn() -> BoxFuture<'static, Fallible>,
+}
+
+impl Job {
+    pub fn interval(&self) -> u64 {
+        get_interval(self.name)
+    }
+}
+
+pub fn jobs() -> Vec<Job> {
+    vec![
+        Job {
+            name: HOT_BOARDS,
+            default_interval: 10,
+            run: || super::hot_boards::run().boxed(),
+        },
+        Job {
+            name: HOT_ARTICLES,
+            default_interval: 60,
+            run: || super::hot_articles::run().boxed(),
+        },
+        Job {
+            name: ELECTION,
+            default_interval: 60,
+            run: || super::election::run().boxed(),
+        },
+    ]
+}
+
+pub fn get_interval(name: &str) -> u64 {
+    match get_config().job.interval.get(name) {
+        Some(interval) => *interval,
+        None => jobs()
+            .into_iter()
+            .find(|job| job.name == name)
+            .map_or(60, |job| job.default_interval),
+    }
+}
+
+/// 第 `failures` 次連續失敗後的等待秒數，每次加倍直到 max_backoff
+fn backoff(interval: u64, failures: u32) -> u64 {
+    let max_backoff = get_config().job.max_backoff.max(interval);
+    interval
+        .checked_mul(1u64 << failures.min(16))
+        .map_or(max_backoff, |wait| wait.min(max_backoff))
+}
+
+/// 執行所有背景工作，永不返回。單一工作失敗只會記錄並延後重試，不影響伺服器與其它工作
+pub async fn start() {
+    let instance = format!(
+        "{}:{}:{}",
+        std::env::var("HOSTNAME").unwrap_or_default(),
+        std::process::id(),
+        rand::thread_rng().gen::<u32>()
+    );
+    log::info!("背景工作實例 {}", instance);
+    join_all(jobs().into_iter().map(|job| run_job(job, &instance))).await;
+}
+
+async fn run_job(job: Job, instance: &str) {
+    let interval = job.interval();
+    let mut failures = 0;
+    loop {
+        let wait = backoff(interval, failures);
+        tokio::time::sleep(Duration::from_secs(wait)).await;
+        // 鎖存活到下一輪之後，持有者停擺時其它實例才能接手
+        let ttl = (wait + backoff(interval, failures + 1)) as usize;
+        match job::acquire_lock(job.name, instance, ttl).await {
+            Ok(true) => (),
+            Ok(false) => {
+                log::trace!("{} 由其它實例執行中", job.name);
+                failures = 0;
+                continue;
+            }
+            Err(err) => {
+                log::warn!("取得 {} 的鎖失敗：{}", job.name, err);
+                failures += 1;
+                continue;
+            }
+        }
+        let result = (job.run)().await;
+        if let Err(err) = &result {
+            failures += 1;
+            log::warn!(
+                "{} 第 {} 次連續失敗，{} 秒後重試：{}",
+                job.name,
+                failures,
+                backoff(interval, failures),
+                err
+            );
+        } else {
+            failures = 0;
+        }
+        if let Err(err) = record(job.name, instance, result, failures).await {
+            log::warn!("記錄 {} 的狀態失敗：{}", job.name, err);
+        }
+    }
+}
+
+async fn record(name: &str, instance: &str, result: Fallible, failures: u32) -> Fallible {
+    let now = Utc::now();
+    let mut status = job::get_status(name).await?.unwrap_or_default();
+    status.instance = instance.to_owned();
+    status.last_run = Some(now);
+    status.failures = failures;
+    match result {
+        Ok(()) => status.last_success = Some(now),
+        Err(err) => {
+            status.last_error = Some(err.to_string());
+            status.last_error_time = Some(now);
+        }
+    }
+    job::set_status(name, &status).await
+}
+
+pub async fn get_status(name: &str) -> Fallible<Option<JobStatus>> {
+    job::get_status(name).await
+}
+pub async fn get_lock_holder(name: &str) -> Fallible<Option<String>> {
+    job::get_lock_holder(name).await
+}
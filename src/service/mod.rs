@@ -1,3 +1,7 @@
+pub mod election;
 pub mod graph_view;
+pub mod hot_articles;
 pub mod hot_boards;
+pub mod job;
 pub mod notification;
+pub mod report;
@@ -13,6 +13,8 @@ fn quality(kind: NotificationKind) -> Option<bool> {
         NotificationKind::ArticleReplied => None,
         NotificationKind::ArticleGoodReplied => Some(true),
         NotificationKind::ArticleBadReplied => Some(false),
+        NotificationKind::ReportResolved => None,
+        NotificationKind::ElectionResult => None,
     }
 }
 
@@ -107,13 +109,16 @@ pub async fn handle_article(
     use force::BasicDataType::*;
     use force::DataType::*;
     for field in category.fields.iter() {
-        // XXX: 如果使用者搞出一個有撞名欄位的分類，這裡的 unwrap 就會爆掉
-        let value = content.remove(&field.name).unwrap();
+        // 選填、投票等欄位可能缺省或爲 null
+        let value = match content.remove(&field.name) {
+            None | Some(Value::Null) => continue,
+            Some(value) => value,
+        };
         match field.datatype {
-            Optional(Bond(_)) | Single(Bond(_)) => {
+            Optional(Bond(..)) | Single(Bond(..)) => {
                 handle!(value);
             }
-            Array { t: Bond(_), .. } => match value {
+            Array { t: Bond(..), .. } => match value {
                 Value::Array(values) => {
                     for value in values {
                         handle!(value);
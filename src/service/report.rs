@@ -0,0 +1,67 @@
+use crate::api::model::{NotificationKind, ReportResolution};
+use crate::custom_error::Fallible;
+use crate::db;
+
+/// 依處置結果隱藏文章或禁言作者，再通知所有檢舉人
+///
+/// 處置與結案在同一交易中，不會有已處置但檢舉仍開著，或兩人重複處置的情形
+pub async fn resolve(
+    resolver_id: i64,
+    report_id: i64,
+    resolution: ReportResolution,
+    ban_days: Option<i64>,
+) -> Fallible {
+    let mut conn = db::get_pool().begin().await?;
+    let target = db::report::lock_target(&mut conn, report_id).await?;
+    db::moderation::check_moderator(target.board_id, resolver_id).await?;
+    let reason = format!("處理檢舉 #{}", report_id);
+    match resolution {
+        ReportResolution::Dismissed => (),
+        ReportResolution::Hidden => {
+            db::moderation::set_hidden_in(
+                &mut conn,
+                target.board_id,
+                resolver_id,
+                target.article_id,
+                true,
+                &reason,
+            )
+            .await?
+        }
+        ReportResolution::Banned => {
+            db::moderation::ban_in(
+                &mut conn,
+                target.board_id,
+                resolver_id,
+                target.author_id,
+                ban_days,
+                &reason,
+            )
+            .await?
+        }
+    }
+    let reporters =
+        db::report::resolve(&mut conn, resolver_id, target.article_id, resolution).await?;
+    conn.commit().await?;
+    // 處置已提交，單一通知失敗只記錄，其餘照發
+    for reporter_id in reporters.into_iter() {
+        if let Err(err) = super::notification::create(
+            reporter_id,
+            NotificationKind::ReportResolved,
+            None,
+            Some(target.board_id),
+            Some(target.article_id),
+            None,
+        )
+        .await
+        {
+            log::warn!(
+                "通知檢舉人 {} 檢舉 #{} 結果失敗：{}",
+                reporter_id,
+                report_id,
+                err
+            );
+        }
+    }
+    Ok(())
+}
@@ -87,7 +87,9 @@ impl Buff {
     fn pre_insert(&self, ty: &BasicDataType) -> Option<(bool, bool)> {
         let (is_block, in_digest) = match ty {
             Text(_) => (true, true),
-            Bond(_) => (true, false),
+            Bond(..) | Image => (true, false),
+            // 票數不在文章內容中，不進摘要
+            Poll(_) => (false, false),
             _ => (false, true),
         };
         if !is_block && self.has_block() {
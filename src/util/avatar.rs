@@ -0,0 +1,194 @@
+use crate::custom_error::{ErrorCode, Fallible};
+use hyper::body::Bytes;
+use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageOutputFormat};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// 大頭貼可供請求的邊長，存放處只存最大的一張，其餘縮放後快取於記憶體
+pub const AVATAR_SIZES: [u32; 3] = [48, 128, 256];
+pub const MAX_AVATAR_SIZE: u32 = AVATAR_SIZES[AVATAR_SIZES.len() - 1];
+// 上傳檔案（解碼 base64 後）的上限
+const MAX_UPLOAD_BYTES: usize = 2 * 1024 * 1024;
+// 解碼前依檔頭擋掉的像素數上限，小檔案也可能宣告極大的尺寸
+const MAX_PIXELS: u64 = 4096 * 4096;
+// 快取縮放結果的數量上限
+const MAX_CACHED: usize = 512;
+
+lazy_static! {
+    static ref DEFAULT_AVATARS: HashMap<u32, Vec<u8>> = {
+        let origin = include_bytes!("../../assets/no-avatar.png");
+        let origin = process(origin).expect("處理預設大頭貼失敗");
+        AVATAR_SIZES
+            .iter()
+            .map(|&size| (size, resize(&origin, size).expect("縮放預設大頭貼失敗")))
+            .collect()
+    };
+    /// 以存放處的鍵與邊長快取縮放過的大頭貼，鍵即內容雜湊，不會過時
+    static ref RESIZED: RwLock<HashMap<(String, u32), Bytes>> = RwLock::new(HashMap::new());
+}
+
+fn invalid<E: std::fmt::Display>(err: E) -> crate::custom_error::Error {
+    ErrorCode::InvalidImage(err.to_string()).to_err()
+}
+
+/// 先讀檔頭確認尺寸，過大者不解碼
+fn decode(data: &[u8]) -> Fallible<DynamicImage> {
+    let reader = || {
+        image::io::Reader::new(std::io::Cursor::new(data))
+            .with_guessed_format()
+            .map_err(invalid)
+    };
+    let (width, height) = reader()?.into_dimensions().map_err(invalid)?;
+    if width as u64 * height as u64 > MAX_PIXELS {
+        return Err(ErrorCode::InvalidImage(format!("圖片尺寸 {}×{} 過大", width, height)).into());
+    }
+    reader()?.decode().map_err(invalid)
+}
+
+fn encode_png(img: &DynamicImage) -> Fallible<Vec<u8>> {
+    let mut buf = Vec::new();
+    img.write_to(&mut buf, ImageOutputFormat::Png)
+        .map_err(invalid)?;
+    Ok(buf)
+}
+
+/// 取不小於請求大小的標準邊長，未指定或超出範圍時取最大者
+pub fn standard_size(size: Option<u32>) -> u32 {
+    match size {
+        Some(size) => AVATAR_SIZES
+            .iter()
+            .copied()
+            .find(|&s| s >= size)
+            .unwrap_or(MAX_AVATAR_SIZE),
+        None => MAX_AVATAR_SIZE,
+    }
+}
+
+/// 檢查上傳的圖片，裁切成置中的正方形並縮放到最大邊長，一律存成 PNG
+pub fn process(data: &[u8]) -> Fallible<Vec<u8>> {
+    if data.len() > MAX_UPLOAD_BYTES {
+        return Err(ErrorCode::InvalidImage(format!(
+            "大頭貼不可超過 {} MB",
+            MAX_UPLOAD_BYTES / 1024 / 1024
+        ))
+        .into());
+    }
+    let img = decode(data)?;
+    let (width, height) = img.dimensions();
+    let side = std::cmp::min(width, height);
+    let img = img.crop_imm((width - side) / 2, (height - side) / 2, side, side);
+    let img = if side > MAX_AVATAR_SIZE {
+        img.resize_exact(MAX_AVATAR_SIZE, MAX_AVATAR_SIZE, FilterType::Lanczos3)
+    } else {
+        img
+    };
+    encode_png(&img)
+}
+
+/// 將處理過的大頭貼縮放至指定邊長，不放大
+pub fn resize(data: &[u8], size: u32) -> Fallible<Vec<u8>> {
+    let img = decode(data)?;
+    if img.width() <= size {
+        return Ok(data.to_vec());
+    }
+    encode_png(&img.resize_exact(size, size, FilterType::Lanczos3))
+}
+
+pub fn get_resized(blob_key: &str, size: u32) -> Option<Bytes> {
+    RESIZED
+        .read()
+        .unwrap()
+        .get(&(blob_key.to_owned(), size))
+        .cloned()
+}
+
+/// 存入快取，滿了就隨意丟掉一筆
+pub fn cache_resized(blob_key: &str, size: u32, data: Vec<u8>) -> Bytes {
+    let data = Bytes::from(data);
+    let mut cache = RESIZED.write().unwrap();
+    if cache.len() >= MAX_CACHED {
+        if let Some(key) = cache.keys().next().cloned() {
+            cache.remove(&key);
+        }
+    }
+    cache.insert((blob_key.to_owned(), size), data.clone());
+    data
+}
+
+pub fn default_avatar(size: u32) -> &'static [u8] {
+    &DEFAULT_AVATARS[&standard_size(Some(size))]
+}
+
+/// 以 SHA-256 計算，不隨編譯器版本改變，客戶端的快取才不會失效
+pub fn etag(data: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        encode_png(&DynamicImage::new_rgb8(width, height)).unwrap()
+    }
+    fn dimensions(data: &[u8]) -> (u32, u32) {
+        image::load_from_memory(data).unwrap().dimensions()
+    }
+
+    #[test]
+    fn test_standard_size() {
+        assert_eq!(standard_size(None), MAX_AVATAR_SIZE);
+        assert_eq!(standard_size(Some(0)), 48);
+        assert_eq!(standard_size(Some(48)), 48);
+        assert_eq!(standard_size(Some(49)), 128);
+        assert_eq!(standard_size(Some(200)), 256);
+        assert_eq!(standard_size(Some(10000)), MAX_AVATAR_SIZE);
+    }
+
+    #[test]
+    fn test_process() {
+        // 裁成置中的正方形，不放大
+        assert_eq!(dimensions(&process(&png(120, 80)).unwrap()), (80, 80));
+        assert_eq!(dimensions(&process(&png(80, 120)).unwrap()), (80, 80));
+        // 超過最大邊長則縮小
+        let max = (MAX_AVATAR_SIZE, MAX_AVATAR_SIZE);
+        assert_eq!(dimensions(&process(&png(600, 400)).unwrap()), max);
+
+        assert!(process(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_pixel_limit() {
+        // 只有一個像素的 GIF ，但檔頭宣告 65535×65535 的畫布
+        let mut gif = Vec::new();
+        DynamicImage::new_rgb8(1, 1)
+            .write_to(&mut gif, ImageOutputFormat::Gif)
+            .unwrap();
+        gif[6..10].copy_from_slice(&[0xff; 4]);
+        match process(&gif).unwrap_err().code() {
+            Some(ErrorCode::InvalidImage(_)) => (),
+            _ => panic!("過大的圖片應被拒絕"),
+        }
+    }
+
+    #[test]
+    fn test_resize() {
+        let data = process(&png(600, 600)).unwrap();
+        assert_eq!(dimensions(&resize(&data, 48).unwrap()), (48, 48));
+        // 不放大
+        let small = process(&png(32, 32)).unwrap();
+        assert_eq!(resize(&small, 48).unwrap(), small);
+    }
+
+    #[test]
+    fn test_etag() {
+        assert_eq!(
+            etag(b""),
+            "\"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\""
+        );
+        assert_eq!(etag(b"abc"), etag(b"abc"));
+        assert_ne!(etag(b"abc"), etag(b"abd"));
+    }
+}
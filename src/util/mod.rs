@@ -6,3 +6,5 @@ pub use article_digest::*;
 
 mod article;
 pub use article::*;
+
+pub mod avatar;
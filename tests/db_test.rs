@@ -1,9 +1,10 @@
 use carbonbond::{
     api::model,
-    config,
+    config::{self, KvStoreKind},
     custom_error::{BondError, DataType, Error, ErrorCode, Fallible},
-    db,
+    db, redis,
 };
+use chrono::{Duration, Utc};
 use force::error::{ValidationError, ValidationErrorCode};
 
 async fn setup() {
@@ -18,6 +19,8 @@ async fn setup() {
     child.wait().unwrap();
     config::init(None);
     db::init().await.unwrap();
+    // 測試不依賴 redis 服務
+    redis::init_with(KvStoreKind::Memory).await.unwrap();
 }
 
 async fn user_test() -> Fallible<(i64, i64)> {
@@ -28,6 +31,11 @@ async fn user_test() -> Fallible<(i64, i64)> {
     assert_eq!(user_id, user.id);
     assert_eq!("測試人", &user.user_name);
 
+    let personal = db::board::get_personal("測試人")
+        .await
+        .expect("註冊後沒有個人看板");
+    assert_eq!(personal.owner_id, Some(user_id));
+
     let code = db::user::get_by_name("測試人2")
         .await
         .unwrap_err()
@@ -140,15 +148,18 @@ async fn article_test(user_id: i64, board_id: i64) -> Fallible {
 
     fn unwrap_bond_err<T>(res: Result<T, Error>) -> BondError {
         if let Err(Error::LogicError {
-            code:
-                ErrorCode::ForceValidate(ValidationError {
-                    code: ValidationErrorCode::Other(e),
-                    ..
-                }),
+            code: ErrorCode::ForceValidate(mut errors),
             ..
         }) = res
         {
-            return e;
+            assert_eq!(errors.len(), 1);
+            if let ValidationError {
+                code: ValidationErrorCode::Other(e),
+                ..
+            } = errors.pop().unwrap()
+            {
+                return e;
+            }
         }
         panic!();
     }
@@ -175,6 +186,56 @@ async fn article_test(user_id: i64, board_id: i64) -> Fallible {
     Ok(())
 }
 
+async fn election_test(board_id: i64, chairman_id: i64) -> Fallible {
+    let opposition_id =
+        db::party::create("測試在野黨", Some("測試板".to_owned()), chairman_id).await?;
+    let now = Utc::now();
+    db::election::open_due(now + Duration::days(1), now + Duration::hours(1)).await?;
+    let election = db::election::get_latest(board_id, None)
+        .await?
+        .expect("沒有舉辦選舉");
+    db::election::register(chairman_id, election.id, opposition_id).await?;
+    sqlx::query("UPDATE parties SET energy = 5 WHERE id = $1")
+        .bind(opposition_id)
+        .execute(db::get_pool())
+        .await?;
+
+    // 無人投票，在野黨靠鍵能勝出
+    let result = db::election::tally(election.id, 1.0)
+        .await?
+        .expect("選舉已開過票");
+    assert_eq!(result.winner_party_id, opposition_id);
+    assert!(result.changed);
+    let board = db::board::get_by_id(board_id).await?;
+    assert_eq!(board.ruling_party_id, opposition_id);
+    assert!(db::election::tally(election.id, 1.0).await?.is_none());
+    Ok(())
+}
+
+async fn kv_store_test(user_id: i64) -> Fallible<()> {
+    let token = "a".repeat(44);
+    assert_eq!(redis::session::get(&token).await?, None);
+    redis::session::create(&token, user_id).await?;
+    assert_eq!(redis::session::get(&token).await?, Some(user_id));
+
+    // 舊版以 token 爲鍵的登入狀態會被搬到新鍵
+    let legacy = "b".repeat(44);
+    redis::get_store()
+        .set(&legacy, &user_id.to_string(), None)
+        .await?;
+    assert_eq!(redis::session::get(&legacy).await?, Some(user_id));
+    assert_eq!(redis::get_store().get(&legacy).await?, None);
+    assert_eq!(redis::session::get(&legacy).await?, Some(user_id));
+
+    assert!(redis::job::acquire_lock("測試工作", "甲", 60).await?);
+    assert!(!redis::job::acquire_lock("測試工作", "乙", 60).await?);
+    assert_eq!(
+        redis::job::get_lock_holder("測試工作").await?,
+        Some("甲".to_owned())
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_db() -> Fallible<()> {
     setup().await;
@@ -188,5 +249,9 @@ async fn test_db() -> Fallible<()> {
     println!("結束通知測試");
     article_test(user, board).await?;
     println!("結束文章測試");
+    election_test(board, user2).await?;
+    println!("結束選舉測試");
+    kv_store_test(user).await?;
+    println!("結束短期資料測試");
     Ok(())
 }